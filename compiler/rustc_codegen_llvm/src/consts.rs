@@ -12,7 +12,8 @@ use rustc_hir as hir;
 use rustc_hir::def_id::DefId;
 use rustc_hir::Node;
 use rustc_middle::middle::codegen_fn_attrs::{CodegenFnAttrFlags, CodegenFnAttrs, SpirVImageTypeSpec,
-                                             SpirVAttrNode, SpirVTypeSpec};
+                                             SpirVAttrNode, SpirVTypeSpec, DenormalMode,
+                                             AmdGpuFpMode};
 use rustc_middle::mir::interpret::{
     read_target_uint, Allocation, ErrorHandled, Pointer,
 };
@@ -395,6 +396,12 @@ impl CodegenCx<'ll, 'tcx> {
         const FLAT_WG_SIZE_KIND: &'static CStr = unsafe {
             CStr::from_bytes_with_nul_unchecked(b"amdgpu-flat-work-group-size\0")
         };
+        const DENORMAL_FP_MATH_KIND: &'static CStr = unsafe {
+            CStr::from_bytes_with_nul_unchecked(b"denormal-fp-math\0")
+        };
+        const DENORMAL_FP_MATH_F32_KIND: &'static CStr = unsafe {
+            CStr::from_bytes_with_nul_unchecked(b"denormal-fp-math-f32\0")
+        };
 
         if self.tcx.sess.target.target.arch != "amdgpu" {
             return;
@@ -426,6 +433,31 @@ impl CodegenCx<'ll, 'tcx> {
             llvm::AddFunctionAttrStringValue(g, idx, FLAT_WG_SIZE_KIND,
                                              attr);
         }
+        let default_fp_mode = || {
+            let name = self.tcx.sess.opts.debugging_opts.amdgpu_fp_mode.as_ref()?;
+            let mode = AmdGpuFpMode::from_name(name).unwrap_or_else(|| {
+                self.tcx.sess.fatal(&format!("unknown AMDGPU fp mode `{}`", name))
+            });
+            Some(mode)
+        };
+        if let Some(fp_mode) = attrs.amdgpu_fp_mode.or_else(default_fp_mode) {
+            // the same mode is used for both outputs and inputs.
+            let mode = |m: DenormalMode| {
+                format!("{0},{0}\0", m.llvm_name())
+            };
+            let f32_mode = mode(fp_mode.f32_denormals);
+            let attr = unsafe {
+                CStr::from_bytes_with_nul_unchecked(f32_mode.as_ref())
+            };
+            llvm::AddFunctionAttrStringValue(g, idx, DENORMAL_FP_MATH_F32_KIND,
+                                             attr);
+            let f64_mode = mode(fp_mode.f64_f16_denormals);
+            let attr = unsafe {
+                CStr::from_bytes_with_nul_unchecked(f64_mode.as_ref())
+            };
+            llvm::AddFunctionAttrStringValue(g, idx, DENORMAL_FP_MATH_KIND,
+                                             attr);
+        }
     }
 
     pub fn add_spirv_metadata(&self, g: &'ll Value, attrs: &CodegenFnAttrs) {
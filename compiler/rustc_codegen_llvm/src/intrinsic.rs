@@ -10,7 +10,7 @@ use crate::value::Value;
 use rustc_codegen_ssa::base::{compare_simd_types, wants_msvc_seh};
 use rustc_codegen_ssa::common::span_invalid_monomorphization_error;
 use rustc_codegen_ssa::common::{IntPredicate, TypeKind};
-use rustc_codegen_ssa::mir::operand::OperandRef;
+use rustc_codegen_ssa::mir::operand::{OperandRef, OperandValue};
 use rustc_codegen_ssa::mir::place::PlaceRef;
 use rustc_codegen_ssa::traits::*;
 use rustc_hir as hir;
@@ -19,7 +19,7 @@ use rustc_middle::ty::{self, Ty};
 use rustc_middle::{bug, span_bug};
 use rustc_span::{sym, symbol::kw, Span, Symbol};
 use rustc_target::abi::{self, HasDataLayout, LayoutOf, Primitive};
-use rustc_target::spec::PanicStrategy;
+use rustc_target::spec::{AddrSpaceKind, PanicStrategy};
 
 use std::cmp::Ordering;
 use std::iter;
@@ -330,6 +330,70 @@ impl IntrinsicCallMethods<'tcx> for Builder<'a, 'll, 'tcx> {
                 return;
             },
 
+            // Loads and stores through the address space tagged pointers in
+            // `core::geobacter::ptr`. The pointer is passed as a flat pointer and is
+            // cast into the segment's address space here, so that LLVM can select
+            // segment specific instructions.
+            // "geobacter_addr_space_<load|store>_<global|constant|local|private>"
+            _ if name_str.starts_with("geobacter_addr_space_") => {
+                let split: Vec<&str> = name_str.split('_').skip(3).collect();
+                if split.len() != 2 {
+                    self.sess().fatal("address space intrinsic not in correct format");
+                }
+
+                let kind = match split[1] {
+                    "global" => AddrSpaceKind::ReadWrite,
+                    "constant" => AddrSpaceKind::ReadOnly,
+                    "local" => AddrSpaceKind::Local,
+                    "private" => AddrSpaceKind::Alloca,
+                    _ => self.sess().fatal("unknown address space in address space intrinsic"),
+                };
+                let flat = self.cx().flat_addr_space();
+                let addr_space = if kind == AddrSpaceKind::Alloca {
+                    // Must agree with where allocas are actually placed.
+                    self.cx().alloca_addr_space()
+                } else {
+                    self.sess().target.target.options.addr_spaces
+                        .get(&kind)
+                        .map(|v| v.index)
+                        .unwrap_or(flat)
+                };
+                // The target spec might not allow casting from flat into this
+                // address space; then the access just stays flat.
+                let addr_space = if self.cx().can_cast_addr_space(flat, addr_space) {
+                    addr_space
+                } else {
+                    flat
+                };
+
+                let layout = self.layout_of(substs.type_at(0));
+                let llptr_ty = self.type_ptr_to(layout.llvm_type(self));
+                let ptr = self.as_ptr_cast(args[0].immediate(), addr_space, llptr_ty);
+                let place = PlaceRef::new_sized(ptr, layout);
+
+                match split[0] {
+                    "load" => {
+                        let val = match layout.abi {
+                            // The constant segment is immutable while the kernel
+                            // runs, so tell LLVM the load is invariant. On AMDGPU
+                            // this (plus uniformity) allows scalar loads.
+                            abi::Abi::Scalar(_) if kind == AddrSpaceKind::ReadOnly => {
+                                let load = self.load(ptr, layout.align.abi);
+                                self.set_invariant_load(load);
+                                OperandValue::Immediate(self.to_immediate(load, layout))
+                            }
+                            _ => self.load_operand(place).val,
+                        };
+                        val.store(self, result);
+                    }
+                    "store" => {
+                        args[1].val.store(self, place);
+                    }
+                    _ => self.sess().fatal("unknown operation in address space intrinsic"),
+                }
+                return;
+            }
+
             sym::amdgcn_dispatch_ptr => {
                 // This intrinsic returns a pointer in the const addr space
                 // which can't be encoded in source level Rust.
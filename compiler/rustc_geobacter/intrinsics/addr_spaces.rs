@@ -0,0 +1,118 @@
+
+use std::mem::size_of;
+
+use rustc_target::abi::{Endian, HasDataLayout};
+use rustc_target::spec::AddrSpaceKind;
+
+use super::*;
+
+/// Returns the target's address space layout as an array of `(index, pointer bits)`
+/// pairs, one per entry in `kinds()`. The layout must match
+/// `core::geobacter::ptr::TargetAddrSpaces`. Missing address spaces are
+/// encoded as `(!0, 0)`.
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug)]
+pub struct AddrSpacesIntrinsic;
+impl AddrSpacesIntrinsic {
+  const LEN: usize = 6;
+
+  fn kinds() -> [AddrSpaceKind; Self::LEN] {
+    [
+      AddrSpaceKind::Flat,
+      AddrSpaceKind::ReadWrite,
+      AddrSpaceKind::ReadOnly,
+      AddrSpaceKind::Local,
+      AddrSpaceKind::Alloca,
+      AddrSpaceKind::Instruction,
+    ]
+  }
+
+  fn data(tcx: TyCtxt<'_>) -> Vec<u8> {
+    let addr_spaces = &tcx.sess.target.target.options.addr_spaces;
+    let dl = tcx.data_layout();
+
+    let mut out = Vec::with_capacity(Self::LEN * 2 * size_of::<u32>());
+    for kind in Self::kinds().iter() {
+      let (idx, bits) = match addr_spaces.get(kind) {
+        Some(props) => {
+          let (size, _) = dl.pointer_info(props.index);
+          (props.index.0, size.bits() as u32)
+        },
+        None => (!0, 0),
+      };
+      for &v in [idx, bits].iter() {
+        match dl.endian {
+          Endian::Little => out.extend_from_slice(&v.to_le_bytes()),
+          Endian::Big => out.extend_from_slice(&v.to_be_bytes()),
+        }
+      }
+    }
+
+    out
+  }
+}
+impl IntrinsicName for AddrSpacesIntrinsic {
+  const NAME: &'static str = "geobacter_addr_spaces";
+}
+impl fmt::Display for AddrSpacesIntrinsic {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "geobacter_addr_spaces")
+  }
+}
+impl mir::CustomIntrinsicMirGen for AddrSpacesIntrinsic {
+  fn mirgen_simple_intrinsic<'tcx>(&self,
+                                   tcx: TyCtxt<'tcx>,
+                                   _instance: Instance<'tcx>,
+                                   mir: &mut mir::Body<'tcx>) {
+    let align = Align::from_bytes(size_of::<u32>() as _).unwrap();
+    let data = Self::data(tcx);
+    let alloc = Allocation::from_bytes(&data[..], align);
+    let alloc = tcx.intern_const_alloc(alloc);
+    let alloc_id = tcx.create_memory_alloc(alloc);
+
+    let ret = Place::return_place();
+
+    let source_info = mir::SourceInfo {
+      span: DUMMY_SP,
+      scope: mir::OUTERMOST_SOURCE_SCOPE,
+    };
+
+    let mut bb = mir::BasicBlockData {
+      statements: Vec::new(),
+      terminator: Some(mir::Terminator {
+        source_info: source_info.clone(),
+        kind: mir::TerminatorKind::Return,
+      }),
+
+      is_cleanup: false,
+    };
+
+    let ptr = Pointer::from(alloc_id);
+    let const_val = ConstValue::Scalar(ptr.into());
+    let constant = tcx.mk_const_op(&source_info, Const {
+      ty: self.output(tcx),
+      val: ConstKind::Value(const_val),
+    });
+    let rvalue = Rvalue::Use(constant);
+
+    let stmt_kind = StatementKind::Assign(Box::new((ret, rvalue)));
+    let stmt = Statement {
+      source_info: source_info.clone(),
+      kind: stmt_kind,
+    };
+    bb.statements.push(stmt);
+    mir.basic_blocks_mut().push(bb);
+  }
+
+  fn generic_parameter_count(&self, _tcx: TyCtxt<'_>) -> usize {
+    0
+  }
+  /// The types of the input args.
+  fn inputs<'tcx>(&self, tcx: TyCtxt<'tcx>) -> &'tcx ty::List<Ty<'tcx>> {
+    tcx.intern_type_list(&[])
+  }
+  /// The return type.
+  fn output<'tcx>(&self, tcx: TyCtxt<'tcx>) -> Ty<'tcx> {
+    let arr = tcx.mk_array(tcx.types.u32, (Self::LEN * 2) as _);
+    tcx.mk_imm_ref(tcx.lifetimes.re_static, arr)
+  }
+}
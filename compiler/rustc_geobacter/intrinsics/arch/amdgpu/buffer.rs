@@ -0,0 +1,133 @@
+//! Raw buffer loads and stores through a buffer resource descriptor (V#).
+
+use super::*;
+
+/// The LLVM intrinsics take the descriptor as a `<4 x i32>`.
+#[repr(simd)]
+#[derive(Clone, Copy)]
+struct Rsrc(u32, u32, u32, u32);
+
+// The cache policy operand is an immediate, so it is fixed to 0 (ie no glc/slc)
+// here instead of being passed through.
+fn amdgcn_raw_buffer_load_dword(rsrc: [u32; 4], voffset: u32, soffset: u32) -> u32 {
+    #[allow(improper_ctypes)]
+    extern "C" {
+        #[link_name = "llvm.amdgcn.raw.buffer.load.i32"]
+        fn amdgcn_raw_buffer_load_i32(rsrc: Rsrc, voffset: u32, soffset: u32,
+                                      cachepolicy: i32) -> u32;
+    }
+    let rsrc = Rsrc(rsrc[0], rsrc[1], rsrc[2], rsrc[3]);
+    unsafe { amdgcn_raw_buffer_load_i32(rsrc, voffset, soffset, 0) }
+}
+fn amdgcn_raw_buffer_store_dword(v: u32, rsrc: [u32; 4], voffset: u32, soffset: u32) {
+    #[allow(improper_ctypes)]
+    extern "C" {
+        #[link_name = "llvm.amdgcn.raw.buffer.store.i32"]
+        fn amdgcn_raw_buffer_store_i32(v: u32, rsrc: Rsrc, voffset: u32, soffset: u32,
+                                       cachepolicy: i32);
+    }
+    let rsrc = Rsrc(rsrc[0], rsrc[1], rsrc[2], rsrc[3]);
+    unsafe { amdgcn_raw_buffer_store_i32(v, rsrc, voffset, soffset, 0) }
+}
+
+fn rsrc_ty<'tcx>(tcx: TyCtxt<'tcx>) -> Ty<'tcx> {
+    tcx.mk_array(tcx.types.u32, 4)
+}
+
+/// fn geobacter_amdgpu_raw_buffer_load_dword(rsrc: [u32; 4], voffset: u32,
+///                                           soffset: u32) -> u32;
+#[derive(Default)]
+pub struct RawBufferLoad;
+impl RawBufferLoad {
+    fn kernel_instance(&self) -> KernelInstanceRef<'static> {
+        amdgcn_raw_buffer_load_dword.kernel_instance()
+    }
+}
+impl CustomIntrinsicMirGen for RawBufferLoad {
+    fn mirgen_simple_intrinsic<'tcx>(&self,
+                                     tcx: TyCtxt<'tcx>,
+                                     _instance: Instance<'tcx>,
+                                     mir: &mut mir::Body<'tcx>)
+    {
+        debug!("mirgen intrinsic {}", self);
+        let args = mir.args_iter()
+            .map(mir::Place::from)
+            .map(Operand::Move)
+            .collect();
+        tcx.call_device_inst_args(mir, move || {
+            target_check(tcx)?;
+            Some((self.kernel_instance(), args))
+        });
+    }
+
+    fn generic_parameter_count(&self, _tcx: TyCtxt<'_>) -> usize {
+        0
+    }
+    /// The types of the input args.
+    fn inputs<'tcx>(&self, tcx: TyCtxt<'tcx>)
+                    -> &'tcx ty::List<Ty<'tcx>>
+    {
+        tcx.intern_type_list(&[rsrc_ty(tcx), tcx.types.u32, tcx.types.u32])
+    }
+    /// The return type.
+    fn output<'tcx>(&self, tcx: TyCtxt<'tcx>) -> Ty<'tcx> {
+        tcx.types.u32
+    }
+}
+impl IntrinsicName for RawBufferLoad {
+    const NAME: &'static str = "geobacter_amdgpu_raw_buffer_load_dword";
+}
+impl fmt::Display for RawBufferLoad {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::NAME)
+    }
+}
+
+/// fn geobacter_amdgpu_raw_buffer_store_dword(v: u32, rsrc: [u32; 4], voffset: u32,
+///                                            soffset: u32);
+#[derive(Default)]
+pub struct RawBufferStore;
+impl RawBufferStore {
+    fn kernel_instance(&self) -> KernelInstanceRef<'static> {
+        amdgcn_raw_buffer_store_dword.kernel_instance()
+    }
+}
+impl CustomIntrinsicMirGen for RawBufferStore {
+    fn mirgen_simple_intrinsic<'tcx>(&self,
+                                     tcx: TyCtxt<'tcx>,
+                                     _instance: Instance<'tcx>,
+                                     mir: &mut mir::Body<'tcx>)
+    {
+        debug!("mirgen intrinsic {}", self);
+        let args = mir.args_iter()
+            .map(mir::Place::from)
+            .map(Operand::Move)
+            .collect();
+        tcx.call_device_inst_args(mir, move || {
+            target_check(tcx)?;
+            Some((self.kernel_instance(), args))
+        });
+    }
+
+    fn generic_parameter_count(&self, _tcx: TyCtxt<'_>) -> usize {
+        0
+    }
+    /// The types of the input args.
+    fn inputs<'tcx>(&self, tcx: TyCtxt<'tcx>)
+                    -> &'tcx ty::List<Ty<'tcx>>
+    {
+        tcx.intern_type_list(&[tcx.types.u32, rsrc_ty(tcx), tcx.types.u32, tcx.types.u32])
+    }
+    /// The return type.
+    fn output<'tcx>(&self, tcx: TyCtxt<'tcx>) -> Ty<'tcx> {
+        tcx.types.unit
+    }
+}
+impl IntrinsicName for RawBufferStore {
+    const NAME: &'static str = "geobacter_amdgpu_raw_buffer_store_dword";
+}
+impl fmt::Display for RawBufferStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::NAME)
+    }
+}
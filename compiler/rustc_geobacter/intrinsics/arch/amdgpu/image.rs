@@ -0,0 +1,276 @@
+//! Image loads, stores and samples through raw image (T#) and sampler (S#)
+//! resource descriptors.
+
+use super::*;
+
+/// The LLVM intrinsics take the image descriptor as a `<8 x i32>`.
+#[repr(simd)]
+#[derive(Clone, Copy)]
+struct ImageRsrc(u32, u32, u32, u32, u32, u32, u32, u32);
+/// ... and the sampler descriptor as a `<4 x i32>`.
+#[repr(simd)]
+#[derive(Clone, Copy)]
+struct SamplerRsrc(u32, u32, u32, u32);
+#[repr(simd)]
+#[derive(Clone, Copy)]
+struct Texel(f32, f32, f32, f32);
+
+impl ImageRsrc {
+    fn new(r: [u32; 8]) -> Self {
+        ImageRsrc(r[0], r[1], r[2], r[3], r[4], r[5], r[6], r[7])
+    }
+}
+impl SamplerRsrc {
+    fn new(r: [u32; 4]) -> Self {
+        SamplerRsrc(r[0], r[1], r[2], r[3])
+    }
+}
+impl Texel {
+    fn new(v: [f32; 4]) -> Self {
+        Texel(v[0], v[1], v[2], v[3])
+    }
+    fn get(self) -> [f32; 4] {
+        [self.0, self.1, self.2, self.3]
+    }
+}
+
+// dmask, unorm, texfailctrl and cachepolicy are immediates, so they are fixed
+// here instead of being passed through: all four channels, normalized sample
+// coordinates (the S# can still force unnormalized ones), no texfail and no
+// glc/slc.
+const DMASK: i32 = 0xf;
+
+fn amdgcn_image_load_2d(rsrc: [u32; 8], s: u32, t: u32) -> [f32; 4] {
+    #[allow(improper_ctypes)]
+    extern "C" {
+        #[link_name = "llvm.amdgcn.image.load.2d.v4f32.i32"]
+        fn load(dmask: i32, s: u32, t: u32, rsrc: ImageRsrc, texfailctrl: i32,
+                cachepolicy: i32) -> Texel;
+    }
+    unsafe { load(DMASK, s, t, ImageRsrc::new(rsrc), 0, 0).get() }
+}
+fn amdgcn_image_load_3d(rsrc: [u32; 8], s: u32, t: u32, r: u32) -> [f32; 4] {
+    #[allow(improper_ctypes)]
+    extern "C" {
+        #[link_name = "llvm.amdgcn.image.load.3d.v4f32.i32"]
+        fn load(dmask: i32, s: u32, t: u32, r: u32, rsrc: ImageRsrc, texfailctrl: i32,
+                cachepolicy: i32) -> Texel;
+    }
+    unsafe { load(DMASK, s, t, r, ImageRsrc::new(rsrc), 0, 0).get() }
+}
+fn amdgcn_image_store_2d(v: [f32; 4], rsrc: [u32; 8], s: u32, t: u32) {
+    #[allow(improper_ctypes)]
+    extern "C" {
+        #[link_name = "llvm.amdgcn.image.store.2d.v4f32.i32"]
+        fn store(v: Texel, dmask: i32, s: u32, t: u32, rsrc: ImageRsrc,
+                 texfailctrl: i32, cachepolicy: i32);
+    }
+    unsafe { store(Texel::new(v), DMASK, s, t, ImageRsrc::new(rsrc), 0, 0) }
+}
+fn amdgcn_image_store_3d(v: [f32; 4], rsrc: [u32; 8], s: u32, t: u32, r: u32) {
+    #[allow(improper_ctypes)]
+    extern "C" {
+        #[link_name = "llvm.amdgcn.image.store.3d.v4f32.i32"]
+        fn store(v: Texel, dmask: i32, s: u32, t: u32, r: u32, rsrc: ImageRsrc,
+                 texfailctrl: i32, cachepolicy: i32);
+    }
+    unsafe { store(Texel::new(v), DMASK, s, t, r, ImageRsrc::new(rsrc), 0, 0) }
+}
+// Compute shaders have no implicit derivatives, so only the explicit LOD 0
+// (`lz`) sample variants are usable.
+fn amdgcn_image_sample_2d(rsrc: [u32; 8], samp: [u32; 4], s: f32, t: f32) -> [f32; 4] {
+    #[allow(improper_ctypes)]
+    extern "C" {
+        #[link_name = "llvm.amdgcn.image.sample.lz.2d.v4f32.f32"]
+        fn sample(dmask: i32, s: f32, t: f32, rsrc: ImageRsrc, samp: SamplerRsrc,
+                  unorm: bool, texfailctrl: i32, cachepolicy: i32) -> Texel;
+    }
+    unsafe {
+        sample(DMASK, s, t, ImageRsrc::new(rsrc), SamplerRsrc::new(samp),
+               false, 0, 0).get()
+    }
+}
+fn amdgcn_image_sample_3d(rsrc: [u32; 8], samp: [u32; 4], s: f32, t: f32,
+                          r: f32) -> [f32; 4] {
+    #[allow(improper_ctypes)]
+    extern "C" {
+        #[link_name = "llvm.amdgcn.image.sample.lz.3d.v4f32.f32"]
+        fn sample(dmask: i32, s: f32, t: f32, r: f32, rsrc: ImageRsrc,
+                  samp: SamplerRsrc, unorm: bool, texfailctrl: i32,
+                  cachepolicy: i32) -> Texel;
+    }
+    unsafe {
+        sample(DMASK, s, t, r, ImageRsrc::new(rsrc), SamplerRsrc::new(samp),
+               false, 0, 0).get()
+    }
+}
+
+pub fn insert_all_intrinsics<F>(mut map: F)
+    where F: for<'a> FnMut(&'a str, Lrc<dyn CustomIntrinsicMirGen>),
+{
+    for &(k, v) in ImageIntrinsic::permutations().iter() {
+        map(k, Lrc::new(v));
+    }
+}
+
+pub fn find_intrinsic(_: TyCtxt<'_>, name: &str)
+    -> Result<(), Lrc<dyn CustomIntrinsicMirGen>>
+{
+    for &(k, v) in ImageIntrinsic::permutations().iter() {
+        if k == name {
+            return Err(Lrc::new(v));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Load,
+    Store,
+    Sample,
+}
+impl Op {
+    fn name(&self) -> &'static str {
+        match self {
+            &Op::Load => "load",
+            &Op::Store => "store",
+            &Op::Sample => "sample",
+        }
+    }
+}
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+#[derive(Debug, Clone, Copy)]
+enum Dim {
+    D2,
+    D3,
+}
+impl Dim {
+    fn name(&self) -> &'static str {
+        match self {
+            &Dim::D2 => "2d",
+            &Dim::D3 => "3d",
+        }
+    }
+    fn coords(&self) -> usize {
+        match self {
+            &Dim::D2 => 2,
+            &Dim::D3 => 3,
+        }
+    }
+}
+impl fmt::Display for Dim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// fn geobacter_amdgpu_image_load_2d(rsrc: [u32; 8], s: u32, t: u32) -> [f32; 4];
+/// fn geobacter_amdgpu_image_store_2d(v: [f32; 4], rsrc: [u32; 8], s: u32, t: u32);
+/// fn geobacter_amdgpu_image_sample_2d(rsrc: [u32; 8], samp: [u32; 4], s: f32,
+///                                     t: f32) -> [f32; 4];
+/// and the same for 3d, with an extra `r` coordinate.
+#[derive(Debug, Clone, Copy)]
+pub struct ImageIntrinsic {
+    op: Op,
+    dim: Dim,
+}
+impl ImageIntrinsic {
+    fn permutations() -> &'static [(&'static str, Self); 6] {
+        const C: &'static [(&'static str, ImageIntrinsic); 6] = &[
+            ("geobacter_amdgpu_image_load_2d",
+             ImageIntrinsic { op: Op::Load, dim: Dim::D2, }, ),
+
+            ("geobacter_amdgpu_image_load_3d",
+             ImageIntrinsic { op: Op::Load, dim: Dim::D3, }, ),
+
+            ("geobacter_amdgpu_image_store_2d",
+             ImageIntrinsic { op: Op::Store, dim: Dim::D2, }, ),
+
+            ("geobacter_amdgpu_image_store_3d",
+             ImageIntrinsic { op: Op::Store, dim: Dim::D3, }, ),
+
+            ("geobacter_amdgpu_image_sample_2d",
+             ImageIntrinsic { op: Op::Sample, dim: Dim::D2, }, ),
+
+            ("geobacter_amdgpu_image_sample_3d",
+             ImageIntrinsic { op: Op::Sample, dim: Dim::D3, }, ),
+        ];
+        C
+    }
+    fn kernel_instance(&self) -> KernelInstanceRef<'static> {
+        match (self.op, self.dim) {
+            (Op::Load, Dim::D2) => amdgcn_image_load_2d.kernel_instance(),
+            (Op::Load, Dim::D3) => amdgcn_image_load_3d.kernel_instance(),
+            (Op::Store, Dim::D2) => amdgcn_image_store_2d.kernel_instance(),
+            (Op::Store, Dim::D3) => amdgcn_image_store_3d.kernel_instance(),
+            (Op::Sample, Dim::D2) => amdgcn_image_sample_2d.kernel_instance(),
+            (Op::Sample, Dim::D3) => amdgcn_image_sample_3d.kernel_instance(),
+        }
+    }
+}
+fn texel_ty<'tcx>(tcx: TyCtxt<'tcx>) -> Ty<'tcx> {
+    tcx.mk_array(tcx.types.f32, 4)
+}
+impl CustomIntrinsicMirGen for ImageIntrinsic {
+    fn mirgen_simple_intrinsic<'tcx>(&self,
+                                     tcx: TyCtxt<'tcx>,
+                                     _instance: Instance<'tcx>,
+                                     mir: &mut mir::Body<'tcx>)
+    {
+        debug!("mirgen intrinsic {}", self);
+        let args = mir.args_iter()
+            .map(mir::Place::from)
+            .map(Operand::Move)
+            .collect();
+        tcx.call_device_inst_args(mir, move || {
+            target_check(tcx)?;
+            Some((self.kernel_instance(), args))
+        });
+    }
+
+    fn generic_parameter_count(&self, _tcx: TyCtxt<'_>) -> usize {
+        0
+    }
+    /// The types of the input args.
+    fn inputs<'tcx>(&self, tcx: TyCtxt<'tcx>)
+                    -> &'tcx ty::List<Ty<'tcx>>
+    {
+        let rsrc = tcx.mk_array(tcx.types.u32, 8);
+        let mut inputs = Vec::with_capacity(6);
+        match self.op {
+            Op::Load => {
+                inputs.push(rsrc);
+                inputs.extend((0..self.dim.coords()).map(|_| tcx.types.u32));
+            },
+            Op::Store => {
+                inputs.push(texel_ty(tcx));
+                inputs.push(rsrc);
+                inputs.extend((0..self.dim.coords()).map(|_| tcx.types.u32));
+            },
+            Op::Sample => {
+                inputs.push(rsrc);
+                inputs.push(tcx.mk_array(tcx.types.u32, 4));
+                inputs.extend((0..self.dim.coords()).map(|_| tcx.types.f32));
+            },
+        }
+        tcx.intern_type_list(&inputs)
+    }
+    /// The return type.
+    fn output<'tcx>(&self, tcx: TyCtxt<'tcx>) -> Ty<'tcx> {
+        match self.op {
+            Op::Store => tcx.types.unit,
+            Op::Load | Op::Sample => texel_ty(tcx),
+        }
+    }
+}
+impl fmt::Display for ImageIntrinsic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "geobacter_amdgpu_image_{}_{}", self.op, self.dim)
+    }
+}
@@ -2,8 +2,10 @@
 use super::*;
 use crate::intrinsics::suicide::Suicide;
 
+pub mod buffer;
 pub mod dpp;
 pub mod grid;
+pub mod image;
 
 pub type AmdGpuSuicide = Suicide<SuicideDetail>;
 
@@ -23,6 +25,9 @@ def_id_intrinsic! {
 }
 def_id_intrinsic!(fn amdgcn_sendmsg(arg0: i32, arg1: u32) => "llvm.amdgcn.s.sendmsg");
 def_id_intrinsic!(fn amdgcn_readfirstlane(arg1: u32) -> u32 => "llvm.amdgcn.readfirstlane");
+def_id_intrinsic!(fn amdgcn_s_memtime() -> u64 => "llvm.amdgcn.s.memtime");
+def_id_intrinsic!(fn amdgcn_s_memrealtime() -> u64 => "llvm.amdgcn.s.memrealtime");
+def_id_intrinsic!(fn amdgcn_wavefront_size() -> u32 => "llvm.amdgcn.wavefrontsize");
 
 /// This one is an actual Rust intrinsic; the LLVM intrinsic returns
 /// a pointer in the constant address space, which we can't correctly
@@ -35,6 +40,27 @@ fn amdgcn_dispatch_ptr() -> *const u8 {
     }
     unsafe { amdgcn_dispatch_ptr() }
 }
+/// The comparison predicate is an immediate operand, so this is always `pred != 0`.
+/// In wave32 mode, the upper 32 bits of the result are zero.
+fn amdgcn_ballot(pred: bool) -> u64 {
+    extern "C" {
+        #[link_name = "llvm.amdgcn.icmp.i64.i32"]
+        fn amdgcn_icmp(lhs: u32, rhs: u32, pred: i32) -> u64;
+    }
+    // 33 == CmpInst::ICMP_NE
+    unsafe { amdgcn_icmp(pred as u32, 0, 33) }
+}
+/// `llvm.amdgcn.s.getreg` requires an immediate operand, so the register field
+/// has to be fixed here instead of being passed through.
+fn amdgcn_trap_status() -> u32 {
+    extern "C" {
+        #[link_name = "llvm.amdgcn.s.getreg"]
+        fn amdgcn_s_getreg(hwreg: i32) -> i32;
+    }
+    // HW_REG_TRAPSTS (id 3), offset 0, 9 bits wide: the EXCP field.
+    const HWREG: i32 = 3 | ((9 - 1) << 11);
+    unsafe { amdgcn_s_getreg(HWREG) as u32 }
+}
 
 pub fn insert_all_intrinsics<F>(mut map: F)
     where F: for<'a> FnMut(&'a str, Lrc<dyn CustomIntrinsicMirGen>),
@@ -44,9 +70,17 @@ pub fn insert_all_intrinsics<F>(mut map: F)
     WaveBarrier::insert_into_map(&mut map);
     SendMsg::insert_into_map(&mut map);
     ReadFirstLane::insert_into_map(&mut map);
+    TrapStatus::insert_into_map(&mut map);
+    MemTime::insert_into_map(&mut map);
+    MemRealTime::insert_into_map(&mut map);
+    WavefrontSize::insert_into_map(&mut map);
+    Ballot::insert_into_map(&mut map);
+    buffer::RawBufferLoad::insert_into_map(&mut map);
+    buffer::RawBufferStore::insert_into_map(&mut map);
     dpp::UpdateDpp::insert_into_map(&mut map);
     dpp::UpdateDppWorkaround::insert_into_map(&mut map);
     grid::insert_all_intrinsics(&mut map);
+    image::insert_all_intrinsics(&mut map);
 }
 
 pub fn find_intrinsic(tcx: TyCtxt<'_>, name: &str)
@@ -64,9 +98,17 @@ pub fn find_intrinsic(tcx: TyCtxt<'_>, name: &str)
     WaveBarrier::check(name)?;
     SendMsg::check(name)?;
     ReadFirstLane::check(name)?;
+    TrapStatus::check(name)?;
+    MemTime::check(name)?;
+    MemRealTime::check(name)?;
+    WavefrontSize::check(name)?;
+    Ballot::check(name)?;
+    buffer::RawBufferLoad::check(name)?;
+    buffer::RawBufferStore::check(name)?;
     dpp::UpdateDpp::check(name)?;
     dpp::UpdateDppWorkaround::check(name)?;
     grid::find_intrinsic(tcx, name)?;
+    image::find_intrinsic(tcx, name)?;
 
     Ok(())
 }
@@ -321,3 +363,225 @@ impl fmt::Display for ReadFirstLane {
         write!(f, "{}", Self::NAME)
     }
 }
+
+#[derive(Default)]
+pub struct TrapStatus;
+impl TrapStatus {
+    fn kernel_instance(&self) -> KernelInstanceRef<'static> {
+        amdgcn_trap_status.kernel_instance()
+    }
+}
+impl CustomIntrinsicMirGen for TrapStatus {
+    fn mirgen_simple_intrinsic<'tcx>(&self,
+                                     tcx: TyCtxt<'tcx>,
+                                     _instance: Instance<'tcx>,
+                                     mir: &mut mir::Body<'tcx>)
+    {
+        debug!("mirgen intrinsic {}", self);
+        tcx.call_device_inst(mir, move || {
+            target_check(tcx)?;
+            Some(self.kernel_instance())
+        });
+    }
+
+    fn generic_parameter_count(&self, _tcx: TyCtxt<'_>) -> usize {
+        0
+    }
+    /// The types of the input args.
+    fn inputs<'tcx>(&self, tcx: TyCtxt<'tcx>)
+                    -> &'tcx ty::List<Ty<'tcx>>
+    {
+        tcx.intern_type_list(&[])
+    }
+    /// The return type.
+    fn output<'tcx>(&self, tcx: TyCtxt<'tcx>) -> Ty<'tcx> {
+        tcx.types.u32
+    }
+}
+impl IntrinsicName for TrapStatus {
+    const NAME: &'static str = "geobacter_amdgpu_trap_status";
+}
+impl fmt::Display for TrapStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::NAME)
+    }
+}
+
+/// The shader clock, ie a 64-bit counter running at the core clock frequency.
+#[derive(Default)]
+pub struct MemTime;
+impl MemTime {
+    fn kernel_instance(&self) -> KernelInstanceRef<'static> {
+        amdgcn_s_memtime.kernel_instance()
+    }
+}
+impl CustomIntrinsicMirGen for MemTime {
+    fn mirgen_simple_intrinsic<'tcx>(&self,
+                                     tcx: TyCtxt<'tcx>,
+                                     _instance: Instance<'tcx>,
+                                     mir: &mut mir::Body<'tcx>)
+    {
+        debug!("mirgen intrinsic {}", self);
+        tcx.call_device_inst(mir, move || {
+            target_check(tcx)?;
+            Some(self.kernel_instance())
+        });
+    }
+
+    fn generic_parameter_count(&self, _tcx: TyCtxt<'_>) -> usize {
+        0
+    }
+    /// The types of the input args.
+    fn inputs<'tcx>(&self, tcx: TyCtxt<'tcx>)
+                    -> &'tcx ty::List<Ty<'tcx>>
+    {
+        tcx.intern_type_list(&[])
+    }
+    /// The return type.
+    fn output<'tcx>(&self, tcx: TyCtxt<'tcx>) -> Ty<'tcx> {
+        tcx.types.u64
+    }
+}
+impl IntrinsicName for MemTime {
+    const NAME: &'static str = "geobacter_amdgpu_s_memtime";
+}
+impl fmt::Display for MemTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::NAME)
+    }
+}
+
+/// The "real time" clock, a 64-bit counter running at a constant frequency which
+/// doesn't depend on the current core clock.
+#[derive(Default)]
+pub struct MemRealTime;
+impl MemRealTime {
+    fn kernel_instance(&self) -> KernelInstanceRef<'static> {
+        amdgcn_s_memrealtime.kernel_instance()
+    }
+}
+impl CustomIntrinsicMirGen for MemRealTime {
+    fn mirgen_simple_intrinsic<'tcx>(&self,
+                                     tcx: TyCtxt<'tcx>,
+                                     _instance: Instance<'tcx>,
+                                     mir: &mut mir::Body<'tcx>)
+    {
+        debug!("mirgen intrinsic {}", self);
+        tcx.call_device_inst(mir, move || {
+            target_check(tcx)?;
+            Some(self.kernel_instance())
+        });
+    }
+
+    fn generic_parameter_count(&self, _tcx: TyCtxt<'_>) -> usize {
+        0
+    }
+    /// The types of the input args.
+    fn inputs<'tcx>(&self, tcx: TyCtxt<'tcx>)
+                    -> &'tcx ty::List<Ty<'tcx>>
+    {
+        tcx.intern_type_list(&[])
+    }
+    /// The return type.
+    fn output<'tcx>(&self, tcx: TyCtxt<'tcx>) -> Ty<'tcx> {
+        tcx.types.u64
+    }
+}
+impl IntrinsicName for MemRealTime {
+    const NAME: &'static str = "geobacter_amdgpu_s_memrealtime";
+}
+impl fmt::Display for MemRealTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::NAME)
+    }
+}
+
+#[derive(Default)]
+pub struct WavefrontSize;
+impl WavefrontSize {
+    fn kernel_instance(&self) -> KernelInstanceRef<'static> {
+        amdgcn_wavefront_size.kernel_instance()
+    }
+}
+impl CustomIntrinsicMirGen for WavefrontSize {
+    fn mirgen_simple_intrinsic<'tcx>(&self,
+                                     tcx: TyCtxt<'tcx>,
+                                     _instance: Instance<'tcx>,
+                                     mir: &mut mir::Body<'tcx>)
+    {
+        debug!("mirgen intrinsic {}", self);
+        tcx.call_device_inst(mir, move || {
+            target_check(tcx)?;
+            Some(self.kernel_instance())
+        });
+    }
+
+    fn generic_parameter_count(&self, _tcx: TyCtxt<'_>) -> usize {
+        0
+    }
+    /// The types of the input args.
+    fn inputs<'tcx>(&self, tcx: TyCtxt<'tcx>)
+                    -> &'tcx ty::List<Ty<'tcx>>
+    {
+        tcx.intern_type_list(&[])
+    }
+    /// The return type.
+    fn output<'tcx>(&self, tcx: TyCtxt<'tcx>) -> Ty<'tcx> {
+        tcx.types.u32
+    }
+}
+impl IntrinsicName for WavefrontSize {
+    const NAME: &'static str = "geobacter_amdgpu_wavefront_size";
+}
+impl fmt::Display for WavefrontSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::NAME)
+    }
+}
+
+#[derive(Default)]
+pub struct Ballot;
+impl Ballot {
+    fn kernel_instance(&self) -> KernelInstanceRef<'static> {
+        amdgcn_ballot.kernel_instance()
+    }
+}
+impl CustomIntrinsicMirGen for Ballot {
+    fn mirgen_simple_intrinsic<'tcx>(&self,
+                                     tcx: TyCtxt<'tcx>,
+                                     _instance: Instance<'tcx>,
+                                     mir: &mut mir::Body<'tcx>)
+    {
+        debug!("mirgen intrinsic {}", self);
+        let args = mir.args_iter()
+            .map(mir::Place::from)
+            .map(Operand::Move)
+            .collect();
+        tcx.call_device_inst_args(mir, move || {
+            target_check(tcx)?;
+            Some((self.kernel_instance(), args))
+        });
+    }
+
+    fn generic_parameter_count(&self, _tcx: TyCtxt<'_>) -> usize {
+        0
+    }
+    /// The types of the input args.
+    fn inputs<'tcx>(&self, tcx: TyCtxt<'tcx>)
+                    -> &'tcx ty::List<Ty<'tcx>>
+    {
+        tcx.intern_type_list(&[tcx.types.bool])
+    }
+    /// The return type.
+    fn output<'tcx>(&self, tcx: TyCtxt<'tcx>) -> Ty<'tcx> {
+        tcx.types.u64
+    }
+}
+impl IntrinsicName for Ballot {
+    const NAME: &'static str = "geobacter_amdgpu_ballot";
+}
+impl fmt::Display for Ballot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::NAME)
+    }
+}
@@ -41,7 +41,9 @@ macro_rules! def_id_intrinsic {
     )
 }
 
-// these three need to be supported always.
+// These are target independent, so they're supported by every stage; the
+// arch specific intrinsics are only available when building stage1+.
+pub mod addr_spaces;
 pub mod kernel;
 pub mod platform;
 pub mod specialization_param;
@@ -83,6 +85,7 @@ pub fn insert_generic_intrinsics<F>(mut map: F)
     kernel::KernelInstance::insert_into_map(&mut map);
     kernel::KernelContextDataId::insert_into_map(&mut map);
     specialization_param::SpecializationParam::insert_into_map(&mut map);
+    addr_spaces::AddrSpacesIntrinsic::insert_into_map(&mut map);
 
     #[cfg(any(stage1, stage2))] {
         arch::insert_all_intrinsics(&mut map);
@@ -106,6 +109,7 @@ fn custom_intrinsic_mirgen(tcx: TyCtxt<'_>, def_id: DefId)
         kernel::KernelContextDataId::check(name)?;
         platform::PlatformIntrinsic::check(name)?;
         specialization_param::SpecializationParam::check(name)?;
+        addr_spaces::AddrSpacesIntrinsic::check(name)?;
 
         #[cfg(any(stage1, stage2))] {
             arch::find_intrinsic(tcx, name)?;
@@ -6,6 +6,7 @@
 #![feature(link_llvm_intrinsics)]
 #![feature(never_type)]
 #![feature(or_patterns)]
+#![feature(repr_simd)]
 
 #![recursion_limit="256"]
 
@@ -33,6 +33,9 @@ pub struct CodegenFnAttrs {
     pub amdgpu_uniform_workgroup_size: Option<bool>,
     /// AMDGPU specific flat workgroup size attribute.
     pub amdgpu_flat_workgroup_size: Option<(usize, usize)>,
+    /// AMDGPU specific floating point denormal handling. This ends up in the
+    /// FP_DENORM fields of the kernel descriptor's mode register.
+    pub amdgpu_fp_mode: Option<AmdGpuFpMode>,
     /// The `#[target_feature(enable = "...")]` attribute and the enabled
     /// features (only enabled features are supported right now).
     pub target_features: Vec<Symbol>,
@@ -111,6 +114,7 @@ impl CodegenFnAttrs {
             amdgpu_num_vgpr: None,
             amdgpu_uniform_workgroup_size: None,
             amdgpu_flat_workgroup_size: None,
+            amdgpu_fp_mode: None,
             target_features: vec![],
             linkage: None,
             link_section: None,
@@ -160,6 +164,63 @@ pub struct SpirVStructMember {
     pub decorations: Vec<(String, Vec<u32>)>,
 }
 
+/// How denormals are treated, on input and on output. The variants match LLVM's
+/// `denormal-fp-math` values.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, HashStable, TyEncodable, TyDecodable)]
+pub enum DenormalMode {
+    /// Denormals are fully supported.
+    Ieee,
+    /// Denormals are flushed to zero, keeping the sign.
+    PreserveSign,
+    /// Denormals are flushed to positive zero. AMDGPU has no such mode; it treats
+    /// this the same as `PreserveSign`, ie as "flush".
+    PositiveZero,
+}
+impl DenormalMode {
+    pub fn llvm_name(self) -> &'static str {
+        match self {
+            DenormalMode::Ieee => "ieee",
+            DenormalMode::PreserveSign => "preserve-sign",
+            DenormalMode::PositiveZero => "positive-zero",
+        }
+    }
+}
+
+/// Note there's no rounding mode here: the AMDGPU backend assumes round to
+/// nearest even everywhere and doesn't support anything else yet.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, HashStable, TyEncodable, TyDecodable)]
+pub struct AmdGpuFpMode {
+    /// `f32` denormal handling. GPUs are much faster if `f32` denormals are flushed.
+    pub f32_denormals: DenormalMode,
+    /// `f64` and `f16` denormal handling; these share a mode register field.
+    pub f64_f16_denormals: DenormalMode,
+}
+impl AmdGpuFpMode {
+    /// Parse the `-Z amdgpu-fp-mode` value.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ftz" => Some(Self::ftz()),
+            "ieee" => Some(Self::ieee()),
+            _ => None,
+        }
+    }
+
+    /// Flush everything to zero.
+    pub fn ftz() -> Self {
+        AmdGpuFpMode {
+            f32_denormals: DenormalMode::PreserveSign,
+            f64_f16_denormals: DenormalMode::PreserveSign,
+        }
+    }
+    /// Support denormals everywhere.
+    pub fn ieee() -> Self {
+        AmdGpuFpMode {
+            f32_denormals: DenormalMode::Ieee,
+            f64_f16_denormals: DenormalMode::Ieee,
+        }
+    }
+}
+
 #[derive(Clone, TyEncodable, TyDecodable, Debug, HashStable)]
 pub enum SpirVTypeSpec {
     Image(SpirVImageTypeSpec),
@@ -833,6 +833,9 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "include crate metadata in crate types which otherwise wouldn't include it"),
     always_encode_mir: bool = (false, parse_bool, [TRACKED],
         "encode MIR of all functions into the crate metadata (default: no)"),
+    amdgpu_fp_mode: Option<String> = (None, parse_opt_string, [TRACKED],
+        "the AMDGPU denormal mode (`ftz` or `ieee`) of functions which don't have \
+        one set by the driver"),
     asm_comments: bool = (false, parse_bool, [TRACKED],
         "generate comments into the assembly (may change behavior) (default: no)"),
     ast_json: bool = (false, parse_bool, [UNTRACKED],
@@ -471,6 +471,10 @@ pub enum AddrSpaceKind {
     ReadWrite,
     /// For Harvard architectures, the program instruction's address space
     Instruction,
+    /// Memory shared by the workitems of a workgroup, eg LDS on AMDGPU.
+    Local,
+    /// Any other target specific address space. These are ignored by rustc;
+    /// they're only visible to the drivers through the target spec.
     Named(String),
 }
 
@@ -483,6 +487,7 @@ impl FromStr for AddrSpaceKind {
             "readonly" => AddrSpaceKind::ReadOnly,
             "readwrite" => AddrSpaceKind::ReadWrite,
             "instruction" => AddrSpaceKind::Instruction,
+            "local" => AddrSpaceKind::Local,
             named => AddrSpaceKind::Named(named.into()),
         })
     }
@@ -495,6 +500,7 @@ impl fmt::Display for AddrSpaceKind {
             &AddrSpaceKind::ReadOnly => "readonly",
             &AddrSpaceKind::ReadWrite => "readwrite",
             &AddrSpaceKind::Instruction => "instruction",
+            &AddrSpaceKind::Local => "local",
             &AddrSpaceKind::Named(ref s) => s,
         })
     }
@@ -584,6 +590,7 @@ impl Default for AddrSpaces {
             AddrSpaceKind::Alloca,
             AddrSpaceKind::Flat,
             AddrSpaceKind::Instruction,
+            AddrSpaceKind::Local,
         ];
 
         let insert = |asp: &mut BTreeMap<_, _>, kind, idx| {
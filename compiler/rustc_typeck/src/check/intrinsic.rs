@@ -129,6 +129,11 @@ pub fn check_intrinsic_type(tcx: TyCtxt<'_>, it: &hir::ForeignItem<'_>) {
     let (n_tps, inputs, output, unsafety) = if name_str.starts_with("atomic_scoped_fence_") {
         // We don't check the scope here as that's "OS" defined.
         (0, Vec::new(), tcx.mk_unit(), hir::Unsafety::Unsafe)
+    } else if name_str.starts_with("geobacter_addr_space_load_") {
+        // The address space is checked during codegen.
+        (1, vec![tcx.mk_imm_ptr(param(0))], param(0), hir::Unsafety::Unsafe)
+    } else if name_str.starts_with("geobacter_addr_space_store_") {
+        (1, vec![tcx.mk_mut_ptr(param(0)), param(0)], tcx.mk_unit(), hir::Unsafety::Unsafe)
     } else if name_str.starts_with("atomic_") {
         let split: Vec<&str> = name_str.split('_').collect();
         assert!(split.len() >= 2, "Atomic intrinsic in an incorrect format");
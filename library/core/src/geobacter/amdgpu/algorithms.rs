@@ -0,0 +1,161 @@
+//! Device side building blocks for parallel algorithms: workgroup and grid wide
+//! exclusive scans, and histograms. These are collective operations: every
+//! workitem of the group has to call them, from uniform control flow, as they
+//! use barriers internally.
+//!
+//! There's no way to allocate workgroup local memory from here, so the
+//! workgroup scratch space is passed in by the caller as an `LdsPtr`. Picking
+//! grid sizes and launching the kernels is up to the host runtime.
+
+use crate::sync::atomic::{AtomicU32, Ordering};
+
+use crate::geobacter::ptr::{GlobalPtr, LdsPtr};
+
+use super::cooperative::{GridGroup, WorkgroupGroup};
+use super::dispatch_packet;
+
+/// Exclusive scan of `v` over the workgroup, in `thread_rank` order. Returns the
+/// combination of the values of all lower ranked workitems (`identity` for rank
+/// zero), and the combination of every value in the workgroup. `op` must be
+/// associative, and `identity` its identity element.
+///
+/// # Safety
+///
+/// `scratch` must point to at least `group.size()` elements of memory shared by
+/// the workgroup, and must not be used by anything else until every workitem
+/// has returned.
+#[inline]
+pub unsafe fn workgroup_exclusive_scan<T, F>(group: &WorkgroupGroup<'_>, scratch: LdsPtr<T>,
+                                             v: T, identity: T, op: F) -> (T, T)
+    where T: Copy,
+          F: Fn(T, T) -> T,
+{
+    let n = group.size();
+    let rank = group.thread_rank();
+    let slot = unsafe { scratch.add(rank) };
+
+    // Hillis-Steele: after the step with offset `o`, every slot holds the
+    // inclusive scan over the `2 * o` workitems ending at it.
+    let mut acc = v;
+    unsafe { slot.write(acc) };
+    group.sync();
+    let mut offset = 1;
+    while offset < n {
+        let other = if rank >= offset {
+            Some(unsafe { scratch.add(rank - offset).read() })
+        } else {
+            None
+        };
+        group.sync();
+        if let Some(other) = other {
+            acc = op(other, acc);
+            unsafe { slot.write(acc) };
+        }
+        group.sync();
+        offset *= 2;
+    }
+
+    let total = unsafe { scratch.add(n - 1).read() };
+    let excl = if rank == 0 {
+        identity
+    } else {
+        unsafe { scratch.add(rank - 1).read() }
+    };
+    // so the scratch can be reused right away.
+    group.sync();
+    (excl, total)
+}
+
+/// Exclusive scan of `v` over the whole grid, ordered by workgroup linear id and
+/// then by rank within the workgroup (which is `GridGroup::thread_rank` order
+/// for 1D grids). Returns the combination of the values of all lower ranked
+/// workitems (`identity` for the first). `op` must be associative, and
+/// `identity` its identity element.
+///
+/// Each workgroup scans its own values, publishes its total into `partials`, and
+/// after a grid wide barrier combines the totals of the workgroups before it.
+///
+/// # Safety
+///
+/// Same as `workgroup_exclusive_scan` for `scratch`. `partials` must point to at
+/// least `workgroup_count().len()` elements of device memory, which must not be
+/// used by anything else during the dispatch.
+#[inline]
+pub unsafe fn grid_exclusive_scan<T, F>(grid: &GridGroup<'_>, scratch: LdsPtr<T>,
+                                        partials: GlobalPtr<T>, v: T, identity: T,
+                                        op: F) -> T
+    where T: Copy,
+          F: Fn(T, T) -> T,
+{
+    let group = grid.workgroup();
+    let rank = group.thread_rank();
+    let workgroup = dispatch_packet().workgroup_linear_id();
+
+    let (local, total) = unsafe {
+        workgroup_exclusive_scan(&group, scratch, v, identity, &op)
+    };
+    if rank == 0 {
+        unsafe { partials.add(workgroup).write(total) };
+    }
+    grid.sync();
+
+    // Combine the totals of the preceding workgroups, `group.size()` at a time.
+    let mut base = identity;
+    let mut start = 0;
+    while start < workgroup {
+        let idx = start + rank;
+        let p = if idx < workgroup {
+            unsafe { partials.add(idx).read() }
+        } else {
+            identity
+        };
+        let (_, chunk) = unsafe {
+            workgroup_exclusive_scan(&group, scratch, p, identity, &op)
+        };
+        base = op(base, chunk);
+        start += group.size();
+    }
+
+    op(base, local)
+}
+
+/// Count `bin` into `bins`, privatized per workgroup: counts are first collected
+/// in `local` (with LDS atomics), and only the non-zero ones are added to `bins`.
+/// Bins outside of `bins` are ignored, so workitems with nothing to count can
+/// pass `usize::MAX`; they still have to call this.
+///
+/// # Safety
+///
+/// `local` must point to at least `bins.len()` counters of memory shared by the
+/// workgroup, and must not be used by anything else until every workitem has
+/// returned.
+#[inline]
+pub unsafe fn histogram(group: &WorkgroupGroup<'_>, local: LdsPtr<AtomicU32>,
+                        bins: &[AtomicU32], bin: usize) {
+    let n = group.size();
+    let rank = group.thread_rank();
+    let local_bin = |i: usize| unsafe { &*local.add(i).as_flat() };
+
+    let mut i = rank;
+    while i < bins.len() {
+        local_bin(i).store(0, Ordering::Relaxed);
+        i += n;
+    }
+    group.sync();
+
+    if bin < bins.len() {
+        local_bin(bin).fetch_add(1, Ordering::Relaxed);
+    }
+    group.sync();
+
+    let mut i = rank;
+    while i < bins.len() {
+        let count = local_bin(i).load(Ordering::Relaxed);
+        if count != 0 {
+            bins[i].fetch_add(count, Ordering::Relaxed);
+        }
+        i += n;
+    }
+    // so `local` can be reused right away.
+    group.sync();
+}
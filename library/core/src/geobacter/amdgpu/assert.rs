@@ -0,0 +1,294 @@
+//! Device side assertions which leave a record the host can read back after
+//! the dispatch finishes. Only the first failure of a dispatch is recorded;
+//! later ones just kill their workitems.
+//!
+//! The runtime is expected to allocate an `AssertSlot` in memory visible to
+//! both sides, pass it to the kernel, and check `AssertSlot::info` when the
+//! dispatch completes; failing the dispatch is up to the runtime. Assertions
+//! are written with the `gpu_assert!` macro, bounds checked indexing with
+//! `checked_index`, and a device panic handler can use
+//! `AssertSlot::record_panic`.
+
+use crate::geobacter::intrinsics::geobacter_suicide;
+use crate::panic::{Location, PanicInfo};
+use crate::sync::atomic::{AtomicU32, Ordering};
+
+use super::dispatch_packet;
+use super::wavefront::LaneMask;
+use super::workitem::{workgroup_ids, workitem_ids};
+
+const EMPTY: u32 = 0;
+const WRITING: u32 = 1;
+const VALID: u32 = 2;
+
+/// Storage for a single assertion failure. All fields are atomics so a slot can
+/// be shared by every workitem of a dispatch.
+#[repr(C)]
+#[derive(Debug)]
+pub struct AssertSlot {
+    state: AtomicU32,
+    kind: AtomicU32,
+    file: AtomicU32,
+    line: AtomicU32,
+    column: AtomicU32,
+    kernel_object_lo: AtomicU32,
+    kernel_object_hi: AtomicU32,
+    workgroup_id: [AtomicU32; 3],
+    workitem_id: [AtomicU32; 3],
+    failed_lo: AtomicU32,
+    failed_hi: AtomicU32,
+    active_lo: AtomicU32,
+    active_hi: AtomicU32,
+}
+
+/// What failed.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum AssertKind {
+    /// A `gpu_assert!`.
+    Assert = 1,
+    /// An out of bounds `checked_index`/`checked_index_mut`.
+    Bounds = 2,
+    /// A panic, recorded by `AssertSlot::record_panic`.
+    Panic = 3,
+}
+impl AssertKind {
+    #[inline(always)]
+    pub const fn from_u32(v: u32) -> Option<Self> {
+        match v {
+            1 => Some(AssertKind::Assert),
+            2 => Some(AssertKind::Bounds),
+            3 => Some(AssertKind::Panic),
+            _ => None,
+        }
+    }
+}
+
+/// A snapshot of a recorded assertion failure.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct AssertInfo {
+    pub kind: AssertKind,
+    /// `file_id` of the source file containing the assertion.
+    pub file: u32,
+    pub line: u32,
+    pub column: u32,
+    /// The `kernel_object` of the dispatch packet, identifying the kernel which
+    /// failed.
+    pub kernel_object: u64,
+    pub workgroup_id: [u32; 3],
+    /// The workitem which wrote the record.
+    pub workitem_id: [u32; 3],
+    /// Every lane of the recording workitem's wavefront which failed the
+    /// assertion.
+    pub failed_lanes: LaneMask,
+    /// The lanes which were active when the assertion was checked. Lanes outside
+    /// this mask didn't evaluate the condition at all.
+    pub active_lanes: LaneMask,
+}
+
+/// The id recorded for a source file: the 32 bit FNV-1a hash of its path, as
+/// returned by `Location::file`. The host can hash the files it knows about to
+/// map an `AssertInfo::file` back to a path.
+pub const fn file_id(file: &str) -> u32 {
+    let bytes = file.as_bytes();
+    let mut hash = 0x811c_9dc5u32;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+        i += 1;
+    }
+    hash
+}
+
+impl AssertSlot {
+    pub const fn new() -> Self {
+        AssertSlot {
+            state: AtomicU32::new(EMPTY),
+            kind: AtomicU32::new(0),
+            file: AtomicU32::new(0),
+            line: AtomicU32::new(0),
+            column: AtomicU32::new(0),
+            kernel_object_lo: AtomicU32::new(0),
+            kernel_object_hi: AtomicU32::new(0),
+            workgroup_id: [AtomicU32::new(0), AtomicU32::new(0), AtomicU32::new(0)],
+            workitem_id: [AtomicU32::new(0), AtomicU32::new(0), AtomicU32::new(0)],
+            failed_lo: AtomicU32::new(0),
+            failed_hi: AtomicU32::new(0),
+            active_lo: AtomicU32::new(0),
+            active_hi: AtomicU32::new(0),
+        }
+    }
+
+    /// Returns the recorded failure, if any. Only meaningful once the dispatch
+    /// has finished.
+    pub fn info(&self) -> Option<AssertInfo> {
+        if self.state.load(Ordering::Acquire) != VALID {
+            return None;
+        }
+
+        let ld = |v: &AtomicU32| v.load(Ordering::Relaxed);
+        let ld64 = |lo: &AtomicU32, hi: &AtomicU32| (ld(hi) as u64) << 32 | ld(lo) as u64;
+        Some(AssertInfo {
+            kind: AssertKind::from_u32(ld(&self.kind))?,
+            file: ld(&self.file),
+            line: ld(&self.line),
+            column: ld(&self.column),
+            kernel_object: ld64(&self.kernel_object_lo, &self.kernel_object_hi),
+            workgroup_id: [
+                ld(&self.workgroup_id[0]),
+                ld(&self.workgroup_id[1]),
+                ld(&self.workgroup_id[2]),
+            ],
+            workitem_id: [
+                ld(&self.workitem_id[0]),
+                ld(&self.workitem_id[1]),
+                ld(&self.workitem_id[2]),
+            ],
+            failed_lanes: LaneMask::from_bits(ld64(&self.failed_lo, &self.failed_hi)),
+            active_lanes: LaneMask::from_bits(ld64(&self.active_lo, &self.active_hi)),
+        })
+    }
+    /// Clear the slot so it can be reused for another dispatch.
+    pub fn reset(&self) {
+        self.state.store(EMPTY, Ordering::Release);
+    }
+
+    /// Write `info` into the slot, unless it already holds a record. Returns
+    /// false if the slot was already taken.
+    pub fn record_info(&self, info: &AssertInfo) -> bool {
+        if self.state.compare_exchange(EMPTY, WRITING, Ordering::Acquire,
+                                       Ordering::Relaxed)
+            .is_err()
+        {
+            return false;
+        }
+
+        let st = |v: &AtomicU32, x: u32| v.store(x, Ordering::Relaxed);
+        let st64 = |lo: &AtomicU32, hi: &AtomicU32, x: u64| {
+            st(lo, x as u32);
+            st(hi, (x >> 32) as u32);
+        };
+        st(&self.kind, info.kind as u32);
+        st(&self.file, info.file);
+        st(&self.line, info.line);
+        st(&self.column, info.column);
+        st64(&self.kernel_object_lo, &self.kernel_object_hi, info.kernel_object);
+        for i in 0..3 {
+            st(&self.workgroup_id[i], info.workgroup_id[i]);
+            st(&self.workitem_id[i], info.workitem_id[i]);
+        }
+        st64(&self.failed_lo, &self.failed_hi, info.failed_lanes.bits());
+        st64(&self.active_lo, &self.active_hi, info.active_lanes.bits());
+
+        self.state.store(VALID, Ordering::Release);
+        true
+    }
+
+    /// Record a panic of the current workitem. Only the location is kept, not
+    /// the message. Meant to be called from the device's panic handler, before
+    /// it kills the workitem.
+    #[track_caller]
+    pub fn record_panic(&self, info: &PanicInfo<'_>) {
+        let lanes = LaneMask::active();
+        match info.location() {
+            Some(loc) => self.record(AssertKind::Panic, loc, lanes, lanes),
+            None => self.record(AssertKind::Panic, Location::caller(), lanes, lanes),
+        }
+    }
+
+    /// Record a failure of the current workitem at `loc`.
+    fn record(&self, kind: AssertKind, loc: &Location<'_>, failed: LaneMask,
+              active: LaneMask)
+    {
+        if self.state.load(Ordering::Relaxed) != EMPTY {
+            // don't bother collecting the info.
+            return;
+        }
+
+        self.record_info(&AssertInfo {
+            kind,
+            file: file_id(loc.file()),
+            line: loc.line(),
+            column: loc.column(),
+            kernel_object: dispatch_packet().kernel_object,
+            workgroup_id: workgroup_ids(),
+            workitem_id: workitem_ids(),
+            failed_lanes: failed,
+            active_lanes: active,
+        });
+    }
+}
+impl Default for AssertSlot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Check `cond` on every active lane. If any lane fails, the failure is
+/// recorded into `slot` (if it's still empty) and the failing workitems are
+/// killed.
+///
+/// The failing lanes are collected with a ballot, which only sees the lanes
+/// active at the call. This may be used under divergent control flow, but then
+/// `AssertInfo::failed_lanes` only covers the lanes in
+/// `AssertInfo::active_lanes`.
+///
+/// ```ignore
+/// gpu_assert!(slot, idx < len);
+/// ```
+pub macro gpu_assert($slot:expr, $cond:expr $(,)?) {
+    $crate::geobacter::amdgpu::assert::check($slot, $cond)
+}
+
+/// The implementation of `gpu_assert!`; the caller location is the macro
+/// invocation.
+#[doc(hidden)]
+#[track_caller]
+#[inline(always)]
+pub fn check(slot: &AssertSlot, cond: bool) {
+    let active = LaneMask::active();
+    let failed = LaneMask::ballot(!cond);
+    if failed.is_empty() {
+        return;
+    }
+
+    if !cond {
+        slot.record(AssertKind::Assert, Location::caller(), failed, active);
+        unsafe { geobacter_suicide("gpu_assert failed") }
+    }
+}
+
+/// `&slice[idx]`, but an out of bounds `idx` is recorded into `slot` (as
+/// `AssertKind::Bounds`) and kills the workitem, instead of panicking. Like
+/// `gpu_assert!`, only the active lanes are collected.
+#[track_caller]
+#[inline(always)]
+pub fn checked_index<'a, T>(slot: &AssertSlot, slice: &'a [T], idx: usize) -> &'a T {
+    bounds_check(slot, idx, slice.len());
+    unsafe { slice.get_unchecked(idx) }
+}
+/// `&mut slice[idx]`; see `checked_index`.
+#[track_caller]
+#[inline(always)]
+pub fn checked_index_mut<'a, T>(slot: &AssertSlot, slice: &'a mut [T], idx: usize)
+    -> &'a mut T
+{
+    bounds_check(slot, idx, slice.len());
+    unsafe { slice.get_unchecked_mut(idx) }
+}
+#[track_caller]
+#[inline(always)]
+fn bounds_check(slot: &AssertSlot, idx: usize, len: usize) {
+    let ok = idx < len;
+    let active = LaneMask::active();
+    let failed = LaneMask::ballot(!ok);
+    if failed.is_empty() {
+        return;
+    }
+
+    if !ok {
+        slot.record(AssertKind::Bounds, Location::caller(), failed, active);
+        unsafe { geobacter_suicide("index out of bounds") }
+    }
+}
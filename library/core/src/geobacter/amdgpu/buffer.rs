@@ -0,0 +1,186 @@
+//! Buffer resource descriptors (V#) and raw buffer loads/stores. Buffer
+//! instructions are range checked by the hardware: loads outside of the buffer
+//! return zero and stores outside of it are dropped, so no branch is required
+//! for the common "guard against reading past the end" pattern in gathers and
+//! scatters.
+//!
+//! The descriptor layout here is the GFX8/GFX9 one, which matches the GPUs
+//! listed in `platform::hsa::AmdGcn`.
+
+use crate::geobacter::intrinsics::{geobacter_amdgpu_raw_buffer_load_dword,
+                                   geobacter_amdgpu_raw_buffer_store_dword};
+use crate::marker::{Copy, PhantomData};
+use crate::mem::{size_of, transmute};
+
+use super::ensure_amdgpu;
+
+/// dst_sel_{x,y,z,w} = {x,y,z,w}, num_format = float, data_format = 32. The
+/// formats are only used by typed buffer instructions; the raw ones we use just
+/// need *some* valid format.
+const DWORD3: u32 = 0x0002_7FAC;
+
+/// Element types which can be loaded/stored with a single dword buffer access.
+pub unsafe trait BufferElement: Copy + 'static {
+    fn to_dword(self) -> u32;
+    fn from_dword(v: u32) -> Self;
+}
+macro_rules! impl_buffer_element {
+    ($($ty:ty,)*) => {$(
+        unsafe impl BufferElement for $ty {
+            #[inline(always)]
+            fn to_dword(self) -> u32 {
+                unsafe { transmute(self) }
+            }
+            #[inline(always)]
+            fn from_dword(v: u32) -> Self {
+                unsafe { transmute(v) }
+            }
+        }
+    )*};
+}
+impl_buffer_element!(u32, i32, f32, );
+
+/// A raw buffer resource descriptor. `T` and `'a` tie the descriptor to the
+/// slice it was created from.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct BufferResource<'a, T>
+    where T: BufferElement,
+{
+    desc: [u32; 4],
+    _m: PhantomData<&'a [T]>,
+}
+impl<'a, T> BufferResource<'a, T>
+    where T: BufferElement,
+{
+    /// Panics if the slice is larger than 4GB, which is the most a single buffer
+    /// descriptor can cover.
+    ///
+    /// # Safety
+    ///
+    /// The slice must be in global memory. A descriptor only keeps the low 48
+    /// bits of the address, which are meaningless for the flat aperture addresses
+    /// of LDS or private (stack) memory; such a descriptor would access unrelated
+    /// global memory.
+    #[inline(always)]
+    pub unsafe fn new(slice: &'a [T]) -> Self {
+        unsafe { Self::from_raw_parts(slice.as_ptr(), slice.len()) }
+    }
+    /// Like `new`, but for `len` elements starting at `ptr`. The pointer is only
+    /// stored in the descriptor.
+    ///
+    /// # Safety
+    ///
+    /// Accesses through the descriptor must be valid for `'a`, and the memory
+    /// must be in global memory, as for `new`.
+    #[inline(always)]
+    pub unsafe fn from_raw_parts(ptr: *const T, len: usize) -> Self {
+        let bytes = len.saturating_mul(size_of::<T>());
+        assert!(bytes <= u32::MAX as usize, "buffer too large for a V#");
+
+        let addr = ptr as usize as u64;
+        BufferResource {
+            desc: [
+                addr as u32,
+                // base_address[47:32]; stride, cache_swizzle and swizzle_enable are 0.
+                (addr >> 32) as u32 & 0xffff,
+                // num_records, in bytes because the stride is 0.
+                bytes as u32,
+                DWORD3,
+            ],
+            _m: PhantomData,
+        }
+    }
+
+    /// The raw descriptor dwords.
+    #[inline(always)]
+    pub fn descriptor(&self) -> [u32; 4] { self.desc }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.desc[2] as usize / size_of::<T>()
+    }
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.desc[2] == 0
+    }
+    /// The byte offset used to access `self[idx]`. Offsets which don't fit are
+    /// clamped to `u32::MAX`, which is always out of bounds.
+    #[inline(always)]
+    pub fn offset_of(idx: usize) -> u32 {
+        idx.saturating_mul(size_of::<T>()).min(u32::MAX as usize) as u32
+    }
+
+    /// Load `self[idx]`, or zero if `idx` is out of bounds.
+    #[inline(always)]
+    pub fn load(&self, idx: usize) -> T {
+        ensure_amdgpu("amdgpu_raw_buffer_load");
+        let offset = Self::offset_of(idx);
+        let v = unsafe {
+            geobacter_amdgpu_raw_buffer_load_dword(self.desc, offset, 0)
+        };
+        T::from_dword(v)
+    }
+    /// Load `self[idx]`, checking the index in software first.
+    #[inline(always)]
+    pub fn get(&self, idx: usize) -> Option<T> {
+        if idx < self.len() {
+            Some(self.load(idx))
+        } else {
+            None
+        }
+    }
+}
+
+/// Like `BufferResource`, but also allows stores.
+#[repr(transparent)]
+#[derive(Debug, Eq, PartialEq, Hash)]
+pub struct BufferResourceMut<'a, T>
+    where T: BufferElement,
+{
+    inner: BufferResource<'a, T>,
+    _m: PhantomData<&'a mut [T]>,
+}
+impl<'a, T> BufferResourceMut<'a, T>
+    where T: BufferElement,
+{
+    /// # Safety
+    ///
+    /// Same as `BufferResource::new`: the slice must be in global memory.
+    #[inline(always)]
+    pub unsafe fn new(slice: &'a mut [T]) -> Self {
+        BufferResourceMut {
+            inner: unsafe { BufferResource::new(slice) },
+            _m: PhantomData,
+        }
+    }
+
+    /// A read only view, borrowing `self` so no stores can happen while it's alive.
+    #[inline(always)]
+    pub fn as_resource(&self) -> BufferResource<'_, T> {
+        BufferResource {
+            desc: self.inner.desc,
+            _m: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize { self.inner.len() }
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool { self.inner.is_empty() }
+    #[inline(always)]
+    pub fn load(&self, idx: usize) -> T { self.inner.load(idx) }
+    #[inline(always)]
+    pub fn get(&self, idx: usize) -> Option<T> { self.inner.get(idx) }
+
+    /// Store `v` to `self[idx]`; does nothing if `idx` is out of bounds.
+    #[inline(always)]
+    pub fn store(&mut self, idx: usize, v: T) {
+        ensure_amdgpu("amdgpu_raw_buffer_store");
+        let offset = BufferResource::<T>::offset_of(idx);
+        unsafe {
+            geobacter_amdgpu_raw_buffer_store_dword(v.to_dword(), self.inner.desc,
+                                                    offset, 0)
+        }
+    }
+}
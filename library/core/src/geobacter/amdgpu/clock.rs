@@ -0,0 +1,50 @@
+//! In-kernel timing. There are two counters:
+//!
+//! * `clock()` (`s_memtime`) counts shader core clock cycles. Its rate changes
+//!   with the GPU's current clock, so it's best for counting cycles spent in a
+//!   region of code, not for measuring time.
+//! * `realtime_clock()` (`s_memrealtime`) runs at a constant frequency, which the
+//!   host can query from the agent (the HSA system timestamp frequency, 100MHz on
+//!   current GPUs). Use this, together with `ticks_to_ns`, to measure time.
+//!
+//! Both are read with scalar memory instructions, so the compiler has to insert a
+//! wait on the scalar memory counter before the value can be used. This makes
+//! them a little heavier than they look.
+
+use crate::geobacter::intrinsics::{geobacter_amdgpu_s_memtime,
+                                   geobacter_amdgpu_s_memrealtime};
+use crate::time::Duration;
+
+use super::ensure_amdgpu;
+
+/// The current shader clock counter.
+#[inline(always)]
+pub fn clock() -> u64 {
+    ensure_amdgpu("amdgpu_s_memtime");
+    unsafe { geobacter_amdgpu_s_memtime() }
+}
+/// The current constant rate ("real time") counter.
+#[inline(always)]
+pub fn realtime_clock() -> u64 {
+    ensure_amdgpu("amdgpu_s_memrealtime");
+    unsafe { geobacter_amdgpu_s_memrealtime() }
+}
+
+/// Convert `ticks` of a counter running at `freq_hz` to nanoseconds. Returns
+/// zero if `freq_hz` is zero. `freq_hz` must be below ~18GHz. Saturates at
+/// `u64::MAX` if the result doesn't fit, ie beyond ~584 years.
+#[inline(always)]
+pub const fn ticks_to_ns(ticks: u64, freq_hz: u64) -> u64 {
+    if freq_hz == 0 {
+        return 0;
+    }
+    // avoid 128-bit division, which the device may not have a builtin for.
+    let secs = ticks / freq_hz;
+    let rem = ticks % freq_hz;
+    secs.saturating_mul(1_000_000_000)
+        .saturating_add(rem * 1_000_000_000 / freq_hz)
+}
+#[inline(always)]
+pub fn ticks_to_duration(ticks: u64, freq_hz: u64) -> Duration {
+    Duration::from_nanos(ticks_to_ns(ticks, freq_hz))
+}
@@ -0,0 +1,124 @@
+//! Cooperative-groups style synchronization. `WorkgroupGroup` is always
+//! available; `GridGroup` additionally requires that *every* workgroup of the
+//! dispatch is resident on the device at the same time (a "persistent" or
+//! "cooperative" launch), otherwise `GridGroup::sync` will deadlock. The grid
+//! size has to be picked by the host accordingly.
+
+use crate::sync::atomic::{AtomicU32, Ordering};
+
+use super::{DispatchPacket, dispatch_packet};
+use super::sync::atomic::{Scope, work_group_barrier};
+
+/// The workitems of the current workgroup.
+#[derive(Clone, Copy, Debug)]
+pub struct WorkgroupGroup<'a> {
+    packet: &'a DispatchPacket,
+}
+impl WorkgroupGroup<'static> {
+    #[inline(always)]
+    pub fn this() -> Self {
+        WorkgroupGroup {
+            packet: dispatch_packet(),
+        }
+    }
+}
+impl<'a> WorkgroupGroup<'a> {
+    /// The number of workitems in the group.
+    #[inline(always)]
+    pub fn size(&self) -> usize {
+        self.packet.workgroup_extent().len()
+    }
+    /// The index of the current workitem in the group.
+    #[inline(always)]
+    pub fn thread_rank(&self) -> usize {
+        self.packet.workitem_linear_id()
+    }
+    /// Wait for every workitem in the group. Memory operations before this call
+    /// are visible to the whole workgroup after it.
+    #[inline(always)]
+    pub fn sync(&self) {
+        work_group_barrier(Scope::WorkGroup, Ordering::Release,
+                           Ordering::Acquire);
+    }
+}
+
+/// Device memory backing a `GridGroup`. This must be zeroed by the host before
+/// the dispatch, and shared by every workgroup in it (ie passed as a kernel
+/// argument). It may be reused by later dispatches if they don't overlap.
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct GridBarrier {
+    arrived: AtomicU32,
+    generation: AtomicU32,
+}
+impl GridBarrier {
+    pub const fn new() -> Self {
+        GridBarrier {
+            arrived: AtomicU32::new(0),
+            generation: AtomicU32::new(0),
+        }
+    }
+}
+
+/// Every workitem of the dispatch.
+#[derive(Clone, Copy, Debug)]
+pub struct GridGroup<'a> {
+    packet: &'a DispatchPacket,
+    barrier: &'a GridBarrier,
+}
+impl<'a> GridGroup<'a> {
+    /// Unsafe because the dispatch must have been launched such that all of its
+    /// workgroups are resident at once.
+    #[inline(always)]
+    pub unsafe fn new(barrier: &'a GridBarrier) -> Self {
+        GridGroup {
+            packet: dispatch_packet(),
+            barrier,
+        }
+    }
+
+    /// The number of workitems in the grid.
+    #[inline(always)]
+    pub fn size(&self) -> usize {
+        self.packet.global_extent().len()
+    }
+    /// The index of the current workitem in the grid.
+    #[inline(always)]
+    pub fn thread_rank(&self) -> usize {
+        self.packet.global_linear_id()
+    }
+    #[inline(always)]
+    pub fn workgroup(&self) -> WorkgroupGroup<'a> {
+        WorkgroupGroup {
+            packet: self.packet,
+        }
+    }
+
+    /// Wait for every workitem in the grid. Memory operations before this call
+    /// are visible to the whole device after it.
+    pub fn sync(&self) {
+        // make our writes visible device wide before arriving.
+        work_group_barrier(Scope::Device, Ordering::Release,
+                           Ordering::Acquire);
+
+        if self.packet.workitem_linear_id() == 0 {
+            let workgroups = self.packet.workgroup_count().len() as u32;
+            let b = self.barrier;
+            // must be read before arriving, or the last workgroup could bump it
+            // before we've seen the old value.
+            let generation = b.generation.load(Ordering::Acquire);
+            if b.arrived.fetch_add(1, Ordering::AcqRel) + 1 == workgroups {
+                b.arrived.store(0, Ordering::Relaxed);
+                b.generation.fetch_add(1, Ordering::Release);
+            } else {
+                while b.generation.load(Ordering::Acquire) == generation {
+                    crate::hint::spin_loop();
+                }
+            }
+        }
+
+        // and now make everyone else's writes visible to the whole workgroup.
+        work_group_barrier(Scope::Device, Ordering::Release,
+                           Ordering::Acquire);
+    }
+}
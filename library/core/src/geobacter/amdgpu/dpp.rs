@@ -1,3 +1,8 @@
+//! Data parallel primitives (DPP) for cross lane data movement within a row (16
+//! lanes) or, for the `wave_*` controls, the whole wavefront. These are the
+//! GFX8/GFX9 DPP controls and assume a 64 lane wavefront. Ballots and lane masks
+//! are in `wavefront`.
+
 use crate::geobacter::intrinsics::*;
 use crate::marker::{Copy, Sized};
 use crate::mem::{transmute, size_of};
@@ -0,0 +1,81 @@
+//! Access to the exception status the hardware accumulates while a wavefront runs.
+//! These flags are set regardless of whether the corresponding trap is enabled in
+//! the `MODE` register, so they can be used to detect "silent" exceptions, eg an
+//! invalid operation which produced a NaN. The flags are per wavefront and sticky;
+//! to get a per dispatch result, merge them into a host visible word with
+//! `accumulate_exceptions` just before the kernel returns.
+
+use crate::geobacter::intrinsics::geobacter_amdgpu_trap_status;
+use crate::sync::atomic::{AtomicU32, Ordering};
+
+use super::ensure_amdgpu;
+
+/// The `EXCP` field of the `TRAPSTS` hardware register.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Exceptions(u32);
+impl Exceptions {
+    pub const INVALID: u32 = 1 << 0;
+    pub const INPUT_DENORMAL: u32 = 1 << 1;
+    pub const DIV_BY_ZERO: u32 = 1 << 2;
+    pub const OVERFLOW: u32 = 1 << 3;
+    pub const UNDERFLOW: u32 = 1 << 4;
+    pub const INEXACT: u32 = 1 << 5;
+    pub const INT_DIV_BY_ZERO: u32 = 1 << 6;
+    pub const ADDR_WATCH: u32 = 1 << 7;
+    pub const MEM_VIOLATION: u32 = 1 << 8;
+
+    /// All floating point exceptions.
+    pub const FP_MASK: u32 = Self::INVALID | Self::INPUT_DENORMAL | Self::DIV_BY_ZERO |
+        Self::OVERFLOW | Self::UNDERFLOW | Self::INEXACT;
+
+    #[inline(always)]
+    pub const fn from_bits(bits: u32) -> Self {
+        Exceptions(bits & 0x1ff)
+    }
+    #[inline(always)]
+    pub const fn bits(self) -> u32 { self.0 }
+
+    #[inline(always)]
+    pub const fn contains(self, bits: u32) -> bool {
+        self.0 & bits == bits
+    }
+    #[inline(always)]
+    pub const fn invalid(self) -> bool { self.contains(Self::INVALID) }
+    #[inline(always)]
+    pub const fn input_denormal(self) -> bool { self.contains(Self::INPUT_DENORMAL) }
+    #[inline(always)]
+    pub const fn div_by_zero(self) -> bool { self.contains(Self::DIV_BY_ZERO) }
+    #[inline(always)]
+    pub const fn overflow(self) -> bool { self.contains(Self::OVERFLOW) }
+    #[inline(always)]
+    pub const fn underflow(self) -> bool { self.contains(Self::UNDERFLOW) }
+    #[inline(always)]
+    pub const fn inexact(self) -> bool { self.contains(Self::INEXACT) }
+    #[inline(always)]
+    pub const fn int_div_by_zero(self) -> bool { self.contains(Self::INT_DIV_BY_ZERO) }
+
+    /// Did any floating point exception occur? Inexact results are so common that
+    /// they are not included here; check `inexact()` separately if you care.
+    #[inline(always)]
+    pub const fn any_fp(self) -> bool {
+        self.0 & (Self::FP_MASK & !Self::INEXACT) != 0
+    }
+}
+
+/// Read the exceptions accumulated by the current wavefront so far.
+#[inline(always)]
+pub fn exceptions() -> Exceptions {
+    ensure_amdgpu("amdgpu_trap_status");
+    Exceptions::from_bits(unsafe { geobacter_amdgpu_trap_status() })
+}
+
+/// OR this wavefront's exceptions into `dst`. Only one lane needs to do this, but
+/// it's harmless if every lane does. `dst` should be zeroed by the host before the
+/// dispatch and read back after it has completed.
+#[inline(always)]
+pub fn accumulate_exceptions(dst: &AtomicU32) {
+    let e = exceptions();
+    if e.bits() != 0 {
+        dst.fetch_or(e.bits(), Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,109 @@
+//! Image resources (T#) and samplers (S#), accessed through the texture units.
+//! The texel format is part of the image descriptor: loads and samples convert
+//! from it to `f32` channels, and stores convert back, in hardware.
+//!
+//! Creating the descriptors (ie from HSA image and sampler handles) is up to the
+//! runtime; these types only wrap the raw descriptor dwords, which are passed to
+//! the kernel like any other argument. The layouts are the GFX8/GFX9 ones.
+
+use crate::geobacter::intrinsics::{geobacter_amdgpu_image_load_2d,
+                                   geobacter_amdgpu_image_load_3d,
+                                   geobacter_amdgpu_image_store_2d,
+                                   geobacter_amdgpu_image_store_3d,
+                                   geobacter_amdgpu_image_sample_2d,
+                                   geobacter_amdgpu_image_sample_3d};
+use crate::marker::PhantomData;
+
+use super::ensure_amdgpu;
+
+/// A raw sampler descriptor. A sampler only holds the filtering and addressing
+/// state; it doesn't reference any memory.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Sampler {
+    desc: [u32; 4],
+}
+impl Sampler {
+    #[inline(always)]
+    pub const fn from_descriptor(desc: [u32; 4]) -> Self {
+        Sampler { desc }
+    }
+    /// The raw descriptor dwords.
+    #[inline(always)]
+    pub const fn descriptor(&self) -> [u32; 4] { self.desc }
+}
+
+macro_rules! image {
+    (@count $($coord:ident)*) => { 0 $(+ image!(@one $coord))* };
+    (@one $coord:ident) => { 1 };
+    ($(#[$attr:meta])* $name:ident, [$($coord:ident),*], $load:ident, $store:ident,
+     $sample:ident) => {
+
+$(#[$attr])*
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct $name<'a> {
+    desc: [u32; 8],
+    _m: PhantomData<&'a ()>,
+}
+impl<'a> $name<'a> {
+    /// # Safety
+    ///
+    /// `desc` must be a valid image descriptor of this dimension, and the image
+    /// memory it references must stay allocated for `'a`.
+    #[inline(always)]
+    pub const unsafe fn from_descriptor(desc: [u32; 8]) -> Self {
+        $name {
+            desc,
+            _m: PhantomData,
+        }
+    }
+    /// The raw descriptor dwords.
+    #[inline(always)]
+    pub const fn descriptor(&self) -> [u32; 8] { self.desc }
+
+    /// Load the texel at the given integer coordinates, without filtering.
+    /// Out of bounds loads return zero.
+    #[inline(always)]
+    pub fn load(&self, [$($coord),*]: [u32; image!(@count $($coord)*)]) -> [f32; 4] {
+        ensure_amdgpu("amdgpu_image_load");
+        unsafe { $load(self.desc, $($coord),*) }
+    }
+    /// Store `v` to the texel at the given integer coordinates. Out of bounds
+    /// stores are dropped.
+    ///
+    /// # Safety
+    ///
+    /// Nothing else may access this texel concurrently, and the image must not
+    /// be read through the texture cache (ie `sample`) in the same dispatch
+    /// after being written.
+    #[inline(always)]
+    pub unsafe fn store(&self, [$($coord),*]: [u32; image!(@count $($coord)*)],
+                        v: [f32; 4]) {
+        ensure_amdgpu("amdgpu_image_store");
+        unsafe { $store(v, self.desc, $($coord),*) }
+    }
+    /// Sample the image at the given coordinates, at LOD 0. The coordinates are
+    /// normalized (ie `0.0..1.0` covers the image), unless the sampler forces
+    /// unnormalized coordinates; filtering and addressing come from the sampler.
+    #[inline(always)]
+    pub fn sample(&self, sampler: Sampler,
+                  [$($coord),*]: [f32; image!(@count $($coord)*)]) -> [f32; 4] {
+        ensure_amdgpu("amdgpu_image_sample");
+        unsafe { $sample(self.desc, sampler.desc, $($coord),*) }
+    }
+}
+
+    };
+}
+
+image!(
+    /// A raw 2D image descriptor.
+    Image2d, [s, t], geobacter_amdgpu_image_load_2d, geobacter_amdgpu_image_store_2d,
+    geobacter_amdgpu_image_sample_2d
+);
+image!(
+    /// A raw 3D image descriptor.
+    Image3d, [s, t, r], geobacter_amdgpu_image_load_3d, geobacter_amdgpu_image_store_3d,
+    geobacter_amdgpu_image_sample_3d
+);
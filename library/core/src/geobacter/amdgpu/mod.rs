@@ -2,9 +2,18 @@
 use crate::geobacter::intrinsics::geobacter_amdgpu_dispatch_ptr;
 use crate::geobacter::platform::platform;
 
+pub mod algorithms;
+pub mod assert;
+pub mod buffer;
+pub mod clock;
+pub mod cooperative;
 pub mod dpp;
+pub mod exception;
+pub mod image;
 pub mod interrupt;
 pub mod sync;
+pub mod uniform;
+pub mod wavefront;
 pub mod workitem;
 
 // HSA queue dispatch packet, as defined in the HSA specification.
@@ -0,0 +1,79 @@
+//! Wave-uniform values. A value is wave-uniform if every active lane of the
+//! wavefront holds the same value. Such values can live in scalar registers and
+//! pointers to them can be loaded through with scalar loads, but the compiler
+//! can only prove uniformity in simple cases (ie kernel arguments). Wrapping a
+//! value in `Uniform` makes it uniform by construction: the value is broadcast
+//! from the first active lane using `v_readfirstlane`, which the backend knows
+//! produces an SGPR.
+//!
+//! Inactive lanes take no part in this: a `Uniform` created under divergent
+//! control flow is only uniform across the lanes which were active at the time,
+//! and must not be assumed uniform after the lanes reconverge.
+
+use crate::cmp::{Eq, PartialEq};
+use crate::fmt;
+use crate::marker::Copy;
+use crate::ops::Deref;
+
+use super::ensure_amdgpu;
+use super::workitem::ReadFirstLane;
+
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct Uniform<T>(T)
+    where T: ReadFirstLane + Copy;
+
+impl<T> Uniform<T>
+    where T: ReadFirstLane + Copy,
+{
+    /// Use the first active lane's value for every lane. If `v` wasn't uniform to
+    /// begin with, the other lanes' values are silently discarded.
+    #[inline(always)]
+    pub fn new(v: T) -> Self {
+        ensure_amdgpu("Uniform::new");
+        Uniform(unsafe { v.read_first_lane() })
+    }
+    /// Like `new`, but panics in the lanes where `v` isn't equal to the first
+    /// active lane's value. Use this in debug builds of a kernel to check that a
+    /// value you *think* is uniform actually is. Only the active lanes are
+    /// checked.
+    ///
+    /// This requires `Eq`, as a uniform NaN would never compare equal to itself;
+    /// verify floats through their bits (ie `f32::to_bits`) instead.
+    #[inline(always)]
+    pub fn new_verified(v: T) -> Self
+        where T: Eq,
+    {
+        let this = Self::new(v);
+        if this.0 != v {
+            panic!("value is not wave-uniform");
+        }
+        this
+    }
+
+    #[inline(always)]
+    pub fn get(self) -> T { self.0 }
+}
+impl<T> Deref for Uniform<T>
+    where T: ReadFirstLane + Copy,
+{
+    type Target = T;
+    #[inline(always)]
+    fn deref(&self) -> &T { &self.0 }
+}
+impl<T> ReadFirstLane for Uniform<T>
+    where T: ReadFirstLane + Copy,
+{
+    /// Already uniform; nothing to do.
+    #[inline(always)]
+    unsafe fn read_first_lane(self) -> Self { self }
+}
+impl<T> fmt::Debug for Uniform<T>
+    where T: ReadFirstLane + Copy + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Uniform")
+            .field(&self.0)
+            .finish()
+    }
+}
@@ -0,0 +1,92 @@
+//! Ballots and lane masks. Every processor the platform enum knows (GFX8/GFX9)
+//! runs wave64, but code which needs the size should still use
+//! `wavefront_size()` instead of assuming 64; the backend folds it to a
+//! constant, so branching on it is free.
+
+use crate::geobacter::intrinsics::{geobacter_amdgpu_wavefront_size,
+                                   geobacter_amdgpu_ballot};
+use crate::iter::{Iterator, FusedIterator};
+
+use super::ensure_amdgpu;
+
+/// The number of lanes in a wavefront: 32 or 64.
+#[inline(always)]
+pub fn wavefront_size() -> u32 {
+    ensure_amdgpu("amdgpu_wavefront_size");
+    unsafe { geobacter_amdgpu_wavefront_size() }
+}
+
+/// A set of lanes of the current wavefront. Bit `n` is lane `n`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct LaneMask(u64);
+impl LaneMask {
+    #[inline(always)]
+    pub const fn from_bits(bits: u64) -> Self { LaneMask(bits) }
+    #[inline(always)]
+    pub const fn bits(self) -> u64 { self.0 }
+
+    /// The lanes for which `pred` is true. Inactive lanes are never included.
+    #[inline(always)]
+    pub fn ballot(pred: bool) -> Self {
+        ensure_amdgpu("amdgpu_ballot");
+        LaneMask(unsafe { geobacter_amdgpu_ballot(pred) })
+    }
+    /// The currently active lanes.
+    #[inline(always)]
+    pub fn active() -> Self {
+        Self::ballot(true)
+    }
+    /// Every lane of a full wavefront, whether active or not.
+    #[inline(always)]
+    pub fn full() -> Self {
+        match wavefront_size() {
+            32 => LaneMask(0xffff_ffff),
+            _ => LaneMask(!0),
+        }
+    }
+
+    #[inline(always)]
+    pub const fn count(self) -> u32 { self.0.count_ones() }
+    #[inline(always)]
+    pub const fn is_empty(self) -> bool { self.0 == 0 }
+    #[inline(always)]
+    pub const fn contains(self, lane: u32) -> bool {
+        lane < 64 && self.0 & (1 << lane) != 0
+    }
+    /// The lowest lane in the set.
+    #[inline(always)]
+    pub const fn first(self) -> Option<u32> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(self.0.trailing_zeros())
+        }
+    }
+    #[inline(always)]
+    pub fn lanes(self) -> Lanes {
+        Lanes(self.0)
+    }
+}
+
+/// The lanes of a `LaneMask`, lowest first.
+#[derive(Clone, Debug)]
+pub struct Lanes(u64);
+impl Iterator for Lanes {
+    type Item = u32;
+    #[inline(always)]
+    fn next(&mut self) -> Option<u32> {
+        if self.0 == 0 {
+            None
+        } else {
+            let lane = self.0.trailing_zeros();
+            self.0 &= self.0 - 1;
+            Some(lane)
+        }
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.0.count_ones() as usize;
+        (n, Some(n))
+    }
+}
+impl FusedIterator for Lanes { }
@@ -1,5 +1,6 @@
 use crate::geobacter::intrinsics::*;
 use crate::intrinsics::transmute;
+use crate::iter::{Iterator, FusedIterator};
 use crate::marker::Copy;
 use crate::mem::size_of;
 use super::{DispatchPacket, ensure_amdgpu};
@@ -177,18 +178,8 @@ impl DispatchPacket {
     }
     #[inline(always)]
     pub fn global_linear_id(&self) -> usize {
-        let [l0, l1, l2] = workitem_ids();
-        let [g0, g1, g2] = workgroup_ids();
-        let [s0, s1, s2] = self.workgroup_sizes();
-        let [n0, n1, _n2] = self.grid_sizes();
-
-        let n0 = n0 as usize;
-        let n1 = n1 as usize;
-
-        let i0 = (g0 * s0 + l0) as usize;
-        let i1 = (g1 * s1 + l1) as usize;
-        let i2 = (g2 * s2 + l2) as usize;
-        (i2 * n1 + i1) * n0 + i0
+        let (x, y, z) = self.global_ids();
+        self.global_extent().linearize([x, y, z])
     }
     #[inline(always)]
     pub fn global_id_x(&self) -> u32 {
@@ -217,6 +208,163 @@ impl DispatchPacket {
     }
 }
 
+/// The size of a grid or workgroup, in workitems.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Extent3 {
+    pub x: u32,
+    pub y: u32,
+    pub z: u32,
+}
+impl Extent3 {
+    #[inline(always)]
+    pub const fn new(x: u32, y: u32, z: u32) -> Self {
+        Extent3 { x, y, z, }
+    }
+    #[inline(always)]
+    pub const fn new_1d(x: u32) -> Self {
+        Extent3::new(x, 1, 1)
+    }
+    #[inline(always)]
+    pub const fn new_2d(x: u32, y: u32) -> Self {
+        Extent3::new(x, y, 1)
+    }
+    /// The total number of workitems.
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.x as usize * self.y as usize * self.z as usize
+    }
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    #[inline(always)]
+    pub const fn contains(&self, [x, y, z]: [u32; 3]) -> bool {
+        x < self.x && y < self.y && z < self.z
+    }
+    /// Flatten the id, row-major with `x` varying fastest.
+    #[inline(always)]
+    pub const fn linearize(&self, [x, y, z]: [u32; 3]) -> usize {
+        (z as usize * self.y as usize + y as usize) * self.x as usize + x as usize
+    }
+    /// The number of `block`s needed to cover `self` along each axis, rounding
+    /// up. Every axis of `block` must be non-zero.
+    #[inline(always)]
+    pub const fn div_ceil(&self, block: Extent3) -> Extent3 {
+        const fn div_ceil(n: u32, s: u32) -> u32 {
+            // `n + s - 1` could overflow.
+            if n == 0 { 0 } else { (n - 1) / s + 1 }
+        }
+        Extent3::new(div_ceil(self.x, block.x),
+                     div_ceil(self.y, block.y),
+                     div_ceil(self.z, block.z))
+    }
+}
+impl From<[u32; 3]> for Extent3 {
+    #[inline(always)]
+    fn from([x, y, z]: [u32; 3]) -> Self {
+        Extent3::new(x, y, z)
+    }
+}
+impl From<Extent3> for [u32; 3] {
+    #[inline(always)]
+    fn from(v: Extent3) -> Self {
+        [v.x, v.y, v.z]
+    }
+}
+
+impl DispatchPacket {
+    #[inline(always)]
+    pub fn global_extent(&self) -> Extent3 {
+        self.grid_sizes().into()
+    }
+    #[inline(always)]
+    pub fn workgroup_extent(&self) -> Extent3 {
+        self.workgroup_sizes().into()
+    }
+    /// The number of workgroups along each axis. The last workgroup along an
+    /// axis may be partial.
+    #[inline(always)]
+    pub fn workgroup_count(&self) -> Extent3 {
+        self.global_extent().div_ceil(self.workgroup_extent())
+    }
+    /// The flattened id of the current workgroup, in `0..workgroup_count().len()`.
+    #[inline(always)]
+    pub fn workgroup_linear_id(&self) -> usize {
+        self.workgroup_count().linearize(workgroup_ids())
+    }
+    /// The flattened id of the current workitem within its workgroup.
+    #[inline(always)]
+    pub fn workitem_linear_id(&self) -> usize {
+        self.workgroup_extent().linearize(workitem_ids())
+    }
+    /// Like `global_linear_id`, but ignores the Z axis.
+    #[inline(always)]
+    pub fn global_linear_id_2d(&self) -> usize {
+        let (x, y, _) = self.global_ids();
+        self.global_extent().linearize([x, y, 0])
+    }
+    /// Is the current workitem's global id inside `extent`? Use this to mask off
+    /// the workitems in the padding of a grid which was rounded up to a multiple of
+    /// the workgroup size.
+    #[inline(always)]
+    pub fn is_in_bounds(&self, extent: Extent3) -> bool {
+        let (x, y, z) = self.global_ids();
+        extent.contains([x, y, z])
+    }
+    /// Iterate over `0..len` using the whole grid, ie the classic grid-stride loop:
+    /// each workitem starts at its global linear id and advances by the total
+    /// number of workitems in the grid.
+    #[inline(always)]
+    pub fn grid_stride_loop(&self, len: usize) -> GridStrideLoop {
+        GridStrideLoop::new(self.global_linear_id(), self.global_extent().len(), len)
+    }
+}
+
+/// See `DispatchPacket::grid_stride_loop`.
+#[derive(Clone, Debug)]
+pub struct GridStrideLoop {
+    next: usize,
+    stride: usize,
+    end: usize,
+}
+impl GridStrideLoop {
+    /// Iterate over `start..end`, advancing by `stride`.
+    #[inline(always)]
+    pub const fn new(start: usize, stride: usize, end: usize) -> Self {
+        GridStrideLoop {
+            next: start,
+            stride,
+            end,
+        }
+    }
+}
+impl Iterator for GridStrideLoop {
+    type Item = usize;
+    #[inline(always)]
+    fn next(&mut self) -> Option<usize> {
+        if self.next < self.end {
+            let r = self.next;
+            self.next = self.next.saturating_add(self.stride);
+            Some(r)
+        } else {
+            None
+        }
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.next >= self.end {
+            (0, Some(0))
+        } else if self.stride == 0 {
+            // a zero stride can only come from an empty (ie invalid) grid.
+            (0, None)
+        } else {
+            let n = (self.end - self.next - 1) / self.stride + 1;
+            (n, Some(n))
+        }
+    }
+}
+impl FusedIterator for GridStrideLoop { }
+
 use crate::geobacter::intrinsics::geobacter_amdgpu_readfirstlane as read_first_lane;
 
 /// This trait requires that Drop is not implemented.
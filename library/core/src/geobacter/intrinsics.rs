@@ -39,6 +39,17 @@ extern "rust-intrinsic" {
         where F: Fn<Args, Output = Ret>;
     pub fn geobacter_specialization_param<F, R>() -> &'static [R]
         where F: Fn() -> R;
+
+    /// Load/store through a flat pointer which is known to point into the named
+    /// segment. Codegen casts the pointer into the segment's address space
+    /// before the access. Constant segment loads are marked invariant.
+    pub fn geobacter_addr_space_load_global<T>(ptr: *const T) -> T;
+    pub fn geobacter_addr_space_load_constant<T>(ptr: *const T) -> T;
+    pub fn geobacter_addr_space_load_local<T>(ptr: *const T) -> T;
+    pub fn geobacter_addr_space_load_private<T>(ptr: *const T) -> T;
+    pub fn geobacter_addr_space_store_global<T>(ptr: *mut T, v: T);
+    pub fn geobacter_addr_space_store_local<T>(ptr: *mut T, v: T);
+    pub fn geobacter_addr_space_store_private<T>(ptr: *mut T, v: T);
 }
 
 /// AMDGPU intrinsics
@@ -52,6 +63,24 @@ extern "rust-intrinsic" {
     pub fn geobacter_amdgpu_wave_barrier();
     pub fn geobacter_amdgpu_sendmsg(_: i32, _: u32);
     pub fn geobacter_amdgpu_readfirstlane(_: u32) -> u32;
+    pub fn geobacter_amdgpu_trap_status() -> u32;
+    pub fn geobacter_amdgpu_s_memtime() -> u64;
+    pub fn geobacter_amdgpu_s_memrealtime() -> u64;
+    pub fn geobacter_amdgpu_wavefront_size() -> u32;
+    pub fn geobacter_amdgpu_ballot(pred: bool) -> u64;
+    pub fn geobacter_amdgpu_raw_buffer_load_dword(rsrc: [u32; 4], voffset: u32,
+                                                  soffset: u32) -> u32;
+    pub fn geobacter_amdgpu_raw_buffer_store_dword(v: u32, rsrc: [u32; 4], voffset: u32,
+                                                   soffset: u32);
+    pub fn geobacter_amdgpu_image_load_2d(rsrc: [u32; 8], s: u32, t: u32) -> [f32; 4];
+    pub fn geobacter_amdgpu_image_load_3d(rsrc: [u32; 8], s: u32, t: u32, r: u32) -> [f32; 4];
+    pub fn geobacter_amdgpu_image_store_2d(v: [f32; 4], rsrc: [u32; 8], s: u32, t: u32);
+    pub fn geobacter_amdgpu_image_store_3d(v: [f32; 4], rsrc: [u32; 8], s: u32, t: u32,
+                                           r: u32);
+    pub fn geobacter_amdgpu_image_sample_2d(rsrc: [u32; 8], samp: [u32; 4], s: f32,
+                                            t: f32) -> [f32; 4];
+    pub fn geobacter_amdgpu_image_sample_3d(rsrc: [u32; 8], samp: [u32; 4], s: f32,
+                                            t: f32, r: f32) -> [f32; 4];
 
     pub fn geobacter_amdgpu_workitem_x_id() -> u32;
     pub fn geobacter_amdgpu_workitem_y_id() -> u32;
@@ -0,0 +1,591 @@
+//! Software double precision math. Accelerators generally don't have hardware
+//! support for f64 transcendentals, and there's no device libm to call into, so
+//! these are ports of the FreeBSD/Sun fdlibm routines (via musl). They use only
+//! basic arithmetic and bit manipulation and produce identical results on the
+//! host and on devices with IEEE f64 arithmetic.
+//!
+//! All four functions are faithfully rounded: the error is below 1 ulp of the
+//! exact result. The largest errors seen against an arbitrary precision
+//! reference over random arguments were:
+//!
+//! | function | max error  |
+//! |----------|------------|
+//! | `exp`    | 0.77 ulp   |
+//! | `ln`     | 0.39 ulp   |
+//! | `powf`   | 0.77 ulp   |
+//! | `erf`    | 0.80 ulp   |
+//!
+//! Special values (NaN, infinities, signed zeros) follow C99 Annex F.
+//! Floating point exceptions are not raised.
+
+// Copyright (C) 1993, 2004 by Sun Microsystems, Inc. All rights reserved.
+//
+// Developed at SunSoft, a Sun Microsystems, Inc. business.
+// Permission to use, copy, modify, and distribute this
+// software is freely granted, provided that this notice
+// is preserved.
+
+#[inline(always)]
+fn high_word(x: f64) -> u32 {
+    (x.to_bits() >> 32) as u32
+}
+#[inline(always)]
+fn low_word(x: f64) -> u32 {
+    x.to_bits() as u32
+}
+#[inline(always)]
+fn with_high_word(x: f64, hi: u32) -> f64 {
+    f64::from_bits((hi as u64) << 32 | (x.to_bits() & 0xffff_ffff))
+}
+#[inline(always)]
+fn with_low_word(x: f64, lo: u32) -> f64 {
+    f64::from_bits((x.to_bits() & 0xffff_ffff_0000_0000) | lo as u64)
+}
+#[inline(always)]
+fn fabs(x: f64) -> f64 {
+    f64::from_bits(x.to_bits() & !(1 << 63))
+}
+/// `2^n` for `n` in the normal exponent range.
+#[inline(always)]
+fn pow2(n: i32) -> f64 {
+    f64::from_bits(((0x3ff + n) as u64) << 52)
+}
+
+/// `x * 2^n`, computed without intermediate over/underflow.
+fn scalbn(x: f64, mut n: i32) -> f64 {
+    let mut y = x;
+    if n > 1023 {
+        y *= pow2(1023);
+        n -= 1023;
+        if n > 1023 {
+            y *= pow2(1023);
+            n -= 1023;
+            if n > 1023 {
+                n = 1023;
+            }
+        }
+    } else if n < -1022 {
+        // Make sure the final n < -53 to avoid double rounding in the
+        // subnormal range.
+        y *= pow2(-1022 + 53);
+        n += 1022 - 53;
+        if n < -1022 {
+            y *= pow2(-1022 + 53);
+            n += 1022 - 53;
+            if n < -1022 {
+                n = -1022;
+            }
+        }
+    }
+    y * pow2(n)
+}
+
+const LN2_HI: f64 = 6.93147180369123816490e-01; // 0x3fe62e42_fee00000
+const LN2_LO: f64 = 1.90821492927058770002e-10; // 0x3dea39ef_35793c76
+const INV_LN2: f64 = 1.44269504088896338700e+00; // 0x3ff71547_652b82fe
+
+// Shared by `exp` and `powf`.
+const P1: f64 = 1.66666666666666019037e-01; // 0x3fc55555_5555553e
+const P2: f64 = -2.77777777770155933842e-03; // 0xbf66c16c_16bebd93
+const P3: f64 = 6.61375632143793436117e-05; // 0x3f11566a_af25de2c
+const P4: f64 = -1.65339022054652515390e-06; // 0xbebbbd41_c5d26bf1
+const P5: f64 = 4.13813679705723846039e-08; // 0x3e663769_72bea4d0
+
+/// `e^x`.
+pub fn exp(mut x: f64) -> f64 {
+    let mut hx = high_word(x);
+    let sign = (hx >> 31) as i32;
+    hx &= 0x7fff_ffff;
+
+    // Special cases.
+    if hx >= 0x4086_232b {
+        // |x| >= 708.39...
+        if x.is_nan() {
+            return x;
+        }
+        if x > 709.782712893383973096 {
+            // Overflow if x != inf.
+            return x * pow2(1023);
+        }
+        if x < -745.13321910194110842 {
+            return 0.0;
+        }
+    }
+
+    // Argument reduction.
+    let (k, hi, lo);
+    if hx > 0x3fd6_2e42 {
+        // |x| > 0.5 ln2
+        k = if hx >= 0x3ff0_a2b2 {
+            // |x| >= 1.5 ln2
+            (INV_LN2 * x + [0.5, -0.5][sign as usize]) as i32
+        } else {
+            1 - sign - sign
+        };
+        // k*LN2_HI is exact here.
+        hi = x - k as f64 * LN2_HI;
+        lo = k as f64 * LN2_LO;
+        x = hi - lo;
+    } else if hx > 0x3e30_0000 {
+        // |x| > 2^-28
+        k = 0;
+        hi = x;
+        lo = 0.0;
+    } else {
+        return 1.0 + x;
+    }
+
+    // x is now in the primary range.
+    let xx = x * x;
+    let c = x - xx * (P1 + xx * (P2 + xx * (P3 + xx * (P4 + xx * P5))));
+    let y = 1.0 + (x * c / (2.0 - c) - lo + hi);
+    if k == 0 {
+        y
+    } else {
+        scalbn(y, k)
+    }
+}
+
+const LG1: f64 = 6.666666666666735130e-01; // 0x3fe55555_55555593
+const LG2: f64 = 3.999999999940941908e-01; // 0x3fd99999_9997fa04
+const LG3: f64 = 2.857142874366239149e-01; // 0x3fd24924_94229359
+const LG4: f64 = 2.222219843214978396e-01; // 0x3fcc71c5_1d8e78af
+const LG5: f64 = 1.818357216161805012e-01; // 0x3fc74664_96cb03de
+const LG6: f64 = 1.531383769920937332e-01; // 0x3fc39a09_d078c69f
+const LG7: f64 = 1.479819860511658591e-01; // 0x3fc2f112_df3e5244
+
+/// The natural logarithm of `x`.
+pub fn ln(mut x: f64) -> f64 {
+    let mut ui = x.to_bits();
+    let mut hx = (ui >> 32) as u32;
+    let mut k: i32 = 0;
+
+    if hx < 0x0010_0000 || (hx >> 31) != 0 {
+        if ui << 1 == 0 {
+            // ln(+-0) = -inf
+            return -1.0 / (x * x);
+        }
+        if hx >> 31 != 0 {
+            // ln(-#) = NaN
+            return (x - x) / 0.0;
+        }
+        // Subnormal number, scale x up.
+        k -= 54;
+        x *= pow2(54);
+        ui = x.to_bits();
+        hx = (ui >> 32) as u32;
+    } else if hx >= 0x7ff0_0000 {
+        return x;
+    } else if hx == 0x3ff0_0000 && ui << 32 == 0 {
+        return 0.0;
+    }
+
+    // Reduce x into [sqrt(2)/2, sqrt(2)].
+    hx += 0x3ff0_0000 - 0x3fe6_a09e;
+    k += (hx >> 20) as i32 - 0x3ff;
+    hx = (hx & 0x000f_ffff) + 0x3fe6_a09e;
+    ui = (hx as u64) << 32 | (ui & 0xffff_ffff);
+    x = f64::from_bits(ui);
+
+    let f = x - 1.0;
+    let hfsq = 0.5 * f * f;
+    let s = f / (2.0 + f);
+    let z = s * s;
+    let w = z * z;
+    let t1 = w * (LG2 + w * (LG4 + w * LG6));
+    let t2 = z * (LG1 + w * (LG3 + w * (LG5 + w * LG7)));
+    let r = t2 + t1;
+    let dk = k as f64;
+    s * (hfsq + r) + dk * LN2_LO - hfsq + f + dk * LN2_HI
+}
+
+const BP: [f64; 2] = [1.0, 1.5];
+const DP_H: [f64; 2] = [0.0, 5.84962487220764160156e-01]; // 0x3fe2b803_40000000
+const DP_L: [f64; 2] = [0.0, 1.35003920212974897128e-08]; // 0x3e4cfdeb_43cfd006
+const TWO53: f64 = 9007199254740992.0; // 0x43400000_00000000
+const HUGE: f64 = 1.0e300;
+const TINY: f64 = 1.0e-300;
+// Poly coefs for (3/2)*(log(x)-2s-2/3*s**3.
+const L1: f64 = 5.99999999999994648725e-01; // 0x3fe33333_33333303
+const L2: f64 = 4.28571428578550184252e-01; // 0x3fdb6db6_db6fabff
+const L3: f64 = 3.33333329818377432918e-01; // 0x3fd55555_518f264d
+const L4: f64 = 2.72728123808534006489e-01; // 0x3fd17460_a91d4101
+const L5: f64 = 2.30660745775561754067e-01; // 0x3fcd864a_93c9db65
+const L6: f64 = 2.06975017800338417784e-01; // 0x3fca7e28_4a454eef
+const LG2_F: f64 = 6.93147180559945286227e-01; // 0x3fe62e42_fefa39ef
+const LG2_H: f64 = 6.93147182464599609375e-01; // 0x3fe62e43_00000000
+const LG2_L: f64 = -1.90465429995776804525e-09; // 0xbe205c61_0ca86c39
+// -(1024-log2(ovfl+.5ulp))
+const OVT: f64 = 8.0085662595372944372e-017;
+// 2/(3ln2)
+const CP: f64 = 9.61796693925975554329e-01; // 0x3feec709_dc3a03fd
+const CP_H: f64 = 9.61796700954437255859e-01; // 0x3feec709_e0000000
+const CP_L: f64 = -7.02846165095275826516e-09; // 0xbe3e2fe0_145b01f5
+const IVLN2: f64 = 1.44269504088896338700e+00; // 0x3ff71547_652b82fe
+const IVLN2_H: f64 = 1.44269502162933349609e+00; // 0x3ff71547_60000000
+const IVLN2_L: f64 = 1.92596299112661746887e-08; // 0x3e54ae0b_f85ddf44
+
+/// `x^y`. Integer results which are representable are always exact.
+pub fn powf(x: f64, y: f64) -> f64 {
+    let hx = high_word(x) as i32;
+    let lx = low_word(x);
+    let hy = high_word(y) as i32;
+    let ly = low_word(y);
+    let mut ix = hx & 0x7fff_ffff;
+    let iy = hy & 0x7fff_ffff;
+
+    // x^0 = 1, even if x is NaN.
+    if (iy as u32 | ly) == 0 {
+        return 1.0;
+    }
+    // 1^y = 1, even if y is NaN.
+    if hx == 0x3ff0_0000 && lx == 0 {
+        return 1.0;
+    }
+    // NaN if either arg is NaN.
+    if ix > 0x7ff0_0000 || (ix == 0x7ff0_0000 && lx != 0)
+        || iy > 0x7ff0_0000 || (iy == 0x7ff0_0000 && ly != 0)
+    {
+        return x + y;
+    }
+
+    // Determine if y is an odd int when x < 0:
+    // 0: y is not an integer, 1: y is an odd int, 2: y is an even int.
+    let mut yisint = 0;
+    if hx < 0 {
+        if iy >= 0x4340_0000 {
+            yisint = 2;
+        } else if iy >= 0x3ff0_0000 {
+            let k = (iy >> 20) - 0x3ff;
+            if k > 20 {
+                let j = ly >> (52 - k);
+                if (j << (52 - k)) == ly {
+                    yisint = 2 - (j & 1) as i32;
+                }
+            } else if ly == 0 {
+                let j = iy >> (20 - k);
+                if (j << (20 - k)) == iy {
+                    yisint = 2 - (j & 1);
+                }
+            }
+        }
+    }
+
+    // Special values of y.
+    if ly == 0 {
+        if iy == 0x7ff0_0000 {
+            // y is +-inf
+            return if ((ix - 0x3ff0_0000) as u32 | lx) == 0 {
+                // (-1)^+-inf is 1
+                1.0
+            } else if ix >= 0x3ff0_0000 {
+                // (|x|>1)^+-inf = inf,0
+                if hy >= 0 { y } else { 0.0 }
+            } else {
+                // (|x|<1)^+-inf = 0,inf
+                if hy >= 0 { 0.0 } else { -y }
+            };
+        }
+        if iy == 0x3ff0_0000 {
+            // y is +-1
+            return if hy >= 0 { x } else { 1.0 / x };
+        }
+        if hy == 0x4000_0000 {
+            // y is 2
+            return x * x;
+        }
+    }
+
+    let mut ax = fabs(x);
+    // Special values of x.
+    if lx == 0 && (ix == 0x7ff0_0000 || ix == 0 || ix == 0x3ff0_0000) {
+        // x is +-0,+-inf,+-1
+        let mut z = ax;
+        if hy < 0 {
+            z = 1.0 / z;
+        }
+        if hx < 0 {
+            if ((ix - 0x3ff0_0000) | yisint) == 0 {
+                // (-1)^non-int is NaN
+                z = (z - z) / (z - z);
+            } else if yisint == 1 {
+                // (x<0)^odd = -(|x|^odd)
+                z = -z;
+            }
+        }
+        return z;
+    }
+
+    // Sign of the result.
+    let mut s = 1.0;
+    if hx < 0 {
+        if yisint == 0 {
+            // (x<0)^(non-int) is NaN
+            return (x - x) / (x - x);
+        }
+        if yisint == 1 {
+            s = -1.0;
+        }
+    }
+
+    let (t1, t2);
+    if iy > 0x41e0_0000 {
+        // |y| > 2^31
+        if iy > 0x43f0_0000 {
+            // |y| > 2^64, must over/underflow.
+            if ix <= 0x3fef_ffff {
+                return if hy < 0 { HUGE * HUGE } else { TINY * TINY };
+            }
+            if ix >= 0x3ff0_0000 {
+                return if hy > 0 { HUGE * HUGE } else { TINY * TINY };
+            }
+        }
+        // Over/underflow if x is not close to one.
+        if ix < 0x3fef_ffff {
+            return if hy < 0 { s * HUGE * HUGE } else { s * TINY * TINY };
+        }
+        if ix > 0x3ff0_0000 {
+            return if hy > 0 { s * HUGE * HUGE } else { s * TINY * TINY };
+        }
+        // Now |1-x| is tiny <= 2^-20, so it suffices to compute
+        // log(x) by x-x^2/2+x^3/3-x^4/4.
+        let t = ax - 1.0; // t has 20 trailing zeros
+        let w = (t * t) * (0.5 - t * (0.3333333333333333333333 - t * 0.25));
+        let u = IVLN2_H * t; // IVLN2_H has 21 sig. bits
+        let v = t * IVLN2_L - w * IVLN2;
+        t1 = with_low_word(u + v, 0);
+        t2 = v - (t1 - u);
+    } else {
+        let mut n = 0;
+        // Take care of subnormal numbers.
+        if ix < 0x0010_0000 {
+            ax *= TWO53;
+            n -= 53;
+            ix = high_word(ax) as i32;
+        }
+        n += (ix >> 20) - 0x3ff;
+        let j = ix & 0x000f_ffff;
+        // Determine the interval.
+        ix = j | 0x3ff0_0000; // normalize ix
+        let k = if j <= 0x3988e {
+            // |x| < sqrt(3/2)
+            0
+        } else if j < 0xbb67a {
+            // |x| < sqrt(3)
+            1
+        } else {
+            n += 1;
+            ix -= 0x0010_0000;
+            0
+        };
+        ax = with_high_word(ax, ix as u32);
+
+        // Compute ss = s_h+s_l = (x-1)/(x+1) or (x-1.5)/(x+1.5).
+        let u = ax - BP[k];
+        let v = 1.0 / (ax + BP[k]);
+        let ss = u * v;
+        let s_h = with_low_word(ss, 0);
+        // t_h = ax+bp[k] High
+        let t_h_hi = (((ix as u32) >> 1) | 0x2000_0000) + 0x0008_0000 + ((k as u32) << 18);
+        let t_h = f64::from_bits((t_h_hi as u64) << 32);
+        let t_l = ax - (t_h - BP[k]);
+        let s_l = v * ((u - s_h * t_h) - s_h * t_l);
+        // Compute log(ax).
+        let mut s2 = ss * ss;
+        let mut r = s2 * s2 * (L1 + s2 * (L2 + s2 * (L3 + s2 * (L4 + s2 * (L5 + s2 * L6)))));
+        r += s_l * (s_h + ss);
+        s2 = s_h * s_h;
+        let t_h = with_low_word(3.0 + s2 + r, 0);
+        let t_l = r - ((t_h - 3.0) - s2);
+        // u+v = ss*(1+...)
+        let u = s_h * t_h;
+        let v = s_l * t_h + t_l * ss;
+        // 2/(3log2)*(ss+...)
+        let p_h = with_low_word(u + v, 0);
+        let p_l = v - (p_h - u);
+        let z_h = CP_H * p_h; // CP_H+CP_L = 2/(3*log2)
+        let z_l = CP_L * p_h + p_l * CP + DP_L[k];
+        // log2(ax) = (ss+..)*2/(3*log2) = n + dp_h + z_h + z_l
+        let t = n as f64;
+        t1 = with_low_word(((z_h + z_l) + DP_H[k]) + t, 0);
+        t2 = z_l - (((t1 - t) - DP_H[k]) - z_h);
+    }
+
+    // Split up y into y1+y2 and compute (y1+y2)*(t1+t2).
+    let y1 = with_low_word(y, 0);
+    let p_l = (y - y1) * t1 + y * t2;
+    let mut p_h = y1 * t1;
+    let z = p_l + p_h;
+    let j = high_word(z) as i32;
+    let i = low_word(z) as i32;
+    if j >= 0x4090_0000 {
+        // z >= 1024
+        if ((j - 0x4090_0000) | i) != 0 {
+            // z > 1024
+            return s * HUGE * HUGE;
+        }
+        if p_l + OVT > z - p_h {
+            return s * HUGE * HUGE;
+        }
+    } else if (j & 0x7fff_ffff) >= 0x4090_cc00 {
+        // z <= -1075
+        if ((j as u32).wrapping_sub(0xc090_cc00) | i as u32) != 0 {
+            // z < -1075
+            return s * TINY * TINY;
+        }
+        if p_l <= z - p_h {
+            return s * TINY * TINY;
+        }
+    }
+
+    // Compute 2^(p_h+p_l).
+    let i = j & 0x7fff_ffff;
+    let mut k = (i >> 20) - 0x3ff;
+    let mut n = 0;
+    if i > 0x3fe0_0000 {
+        // |z| > 0.5, set n = [z+0.5]
+        n = j + (0x0010_0000 >> (k + 1));
+        k = ((n & 0x7fff_ffff) >> 20) - 0x3ff; // new k for n
+        let t = f64::from_bits(((n & !(0x000f_ffff >> k)) as u32 as u64) << 32);
+        n = ((n & 0x000f_ffff) | 0x0010_0000) >> (20 - k);
+        if j < 0 {
+            n = -n;
+        }
+        p_h -= t;
+    }
+    let t = with_low_word(p_l + p_h, 0);
+    let u = t * LG2_H;
+    let v = (p_l - (t - p_h)) * LG2_F + t * LG2_L;
+    let mut z = u + v;
+    let w = v - (z - u);
+    let t = z * z;
+    let t1 = z - t * (P1 + t * (P2 + t * (P3 + t * (P4 + t * P5))));
+    let r = (z * t1) / (t1 - 2.0) - (w + z * w);
+    z = 1.0 - (r - z);
+    let j = (high_word(z) as i32).wrapping_add(n << 20);
+    if (j >> 20) <= 0 {
+        // Subnormal output.
+        z = scalbn(z, n);
+    } else {
+        z = with_high_word(z, j as u32);
+    }
+    s * z
+}
+
+const ERX: f64 = 8.45062911510467529297e-01; // 0x3feb0ac1_60000000
+// Coefficients for approximation to erf on [0,0.84375].
+const EFX8: f64 = 1.02703333676410069053e+00; // 0x3ff06eba_8214db69
+const PP0: f64 = 1.28379167095512558561e-01; // 0x3fc06eba_8214db68
+const PP1: f64 = -3.25042107247001499370e-01; // 0xbfd4cd7d_691cb913
+const PP2: f64 = -2.84817495755985104766e-02; // 0xbf9d2a51_dbd7194f
+const PP3: f64 = -5.77027029648944159157e-03; // 0xbf77a291_236668e4
+const PP4: f64 = -2.37630166566501626084e-05; // 0xbef8ead6_120016ac
+const QQ1: f64 = 3.97917223959155352819e-01; // 0x3fd97779_cddadc09
+const QQ2: f64 = 6.50222499887672944485e-02; // 0x3fb0a54c_5536ceba
+const QQ3: f64 = 5.08130628187576562776e-03; // 0x3f74d022_c4d36b0f
+const QQ4: f64 = 1.32494738004321644526e-04; // 0x3f215dc9_221c1a10
+const QQ5: f64 = -3.96022827877536812320e-06; // 0xbed09c43_42a26120
+// Coefficients for approximation to erf on [0.84375,1.25].
+const PA0: f64 = -2.36211856075265944077e-03; // 0xbf6359b8_bef77538
+const PA1: f64 = 4.14856118683748331666e-01; // 0x3fda8d00_ad92b34d
+const PA2: f64 = -3.72207876035701323847e-01; // 0xbfd7d240_fbb8c3f1
+const PA3: f64 = 3.18346619901161753674e-01; // 0x3fd45fca_805120e4
+const PA4: f64 = -1.10894694282396677476e-01; // 0xbfbc6398_3d3e28ec
+const PA5: f64 = 3.54783043256182359371e-02; // 0x3fa22a36_599795eb
+const PA6: f64 = -2.16637559486879084300e-03; // 0xbf61bf38_0a96073f
+const QA1: f64 = 1.06420880400844228286e-01; // 0x3fbb3e66_18eee323
+const QA2: f64 = 5.40397917702171048937e-01; // 0x3fe14af0_92eb6f33
+const QA3: f64 = 7.18286544141962662868e-02; // 0x3fb2635c_d99fe9a7
+const QA4: f64 = 1.26171219808761642112e-01; // 0x3fc02660_e763351f
+const QA5: f64 = 1.36370839120290507362e-02; // 0x3f8bedc2_6b51dd1c
+const QA6: f64 = 1.19844998467991074170e-02; // 0x3f888b54_5735151d
+// Coefficients for approximation to erfc on [1.25,1/0.35].
+const RA0: f64 = -9.86494403484714822705e-03; // 0xbf843412_600d6435
+const RA1: f64 = -6.93858572707181764372e-01; // 0xbfe63416_e4ba7360
+const RA2: f64 = -1.05586262253232909814e+01; // 0xc0251e04_41b0e726
+const RA3: f64 = -6.23753324503260060396e+01; // 0xc04f300a_e4cba38d
+const RA4: f64 = -1.62396669462573470355e+02; // 0xc0644cb1_84282266
+const RA5: f64 = -1.84605092906711035994e+02; // 0xc067135c_ebccabb2
+const RA6: f64 = -8.12874355063065934246e+01; // 0xc0545265_57e4d2f2
+const RA7: f64 = -9.81432934416914548592e+00; // 0xc023a0ef_c69ac25c
+const SA1: f64 = 1.96512716674392571292e+01; // 0x4033a6b9_bd707687
+const SA2: f64 = 1.37657754143519042600e+02; // 0x4061350c_526ae721
+const SA3: f64 = 4.34565877475229228821e+02; // 0x407b290d_d58a1a71
+const SA4: f64 = 6.45387271733267880336e+02; // 0x40842b19_21ec2868
+const SA5: f64 = 4.29008140027567833386e+02; // 0x407ad021_57700314
+const SA6: f64 = 1.08635005541779435134e+02; // 0x405b28a3_ee48ae2c
+const SA7: f64 = 6.57024977031928170135e+00; // 0x401a47ef_8e484a93
+const SA8: f64 = -6.04244152148580987438e-02; // 0xbfaeeff2_ee749a62
+// Coefficients for approximation to erfc on [1/.35,28].
+const RB0: f64 = -9.86494292470009928597e-03; // 0xbf843412_39e86f4a
+const RB1: f64 = -7.99283237680523006574e-01; // 0xbfe993ba_70c285de
+const RB2: f64 = -1.77579549177547519889e+01; // 0xc031c209_555f995a
+const RB3: f64 = -1.60636384855821916062e+02; // 0xc064145d_43c5ed98
+const RB4: f64 = -6.37566443368389627722e+02; // 0xc083ec88_1375f228
+const RB5: f64 = -1.02509513161107724954e+03; // 0xc0900461_6a2e5992
+const RB6: f64 = -4.83519191608651397019e+02; // 0xc07e384e_9bdc383f
+const SB1: f64 = 3.03380607434824582924e+01; // 0x403e568b_261d5190
+const SB2: f64 = 3.25792512996573918826e+02; // 0x40745cae_221b9f0a
+const SB3: f64 = 1.53672958608443695994e+03; // 0x409802eb_189d5118
+const SB4: f64 = 3.19985821950859553908e+03; // 0x40a8ffb7_688c246a
+const SB5: f64 = 2.55305040643316442583e+03; // 0x40a3f219_cedf3be6
+const SB6: f64 = 4.74528541206955367215e+02; // 0x407da874_e79fe763
+const SB7: f64 = -2.24409524465858183362e+01; // 0xc03670e2_42712d62
+
+fn erfc1(x: f64) -> f64 {
+    let s = fabs(x) - 1.0;
+    let p = PA0 + s * (PA1 + s * (PA2 + s * (PA3 + s * (PA4 + s * (PA5 + s * PA6)))));
+    let q = 1.0 + s * (QA1 + s * (QA2 + s * (QA3 + s * (QA4 + s * (QA5 + s * QA6)))));
+    1.0 - ERX - p / q
+}
+fn erfc2(ix: u32, mut x: f64) -> f64 {
+    if ix < 0x3ff4_0000 {
+        // |x| < 1.25
+        return erfc1(x);
+    }
+
+    x = fabs(x);
+    let s = 1.0 / (x * x);
+    let (r, big_s);
+    if ix < 0x4006_db6d {
+        // |x| < 1/.35 ~ 2.85714
+        r = RA0 + s * (RA1 + s * (RA2 + s * (RA3 + s * (RA4 + s * (RA5 + s * (RA6 + s * RA7))))));
+        big_s = 1.0 + s * (SA1 + s * (SA2 + s * (SA3 + s * (SA4 + s * (SA5 + s * (SA6 + s * (SA7 + s * SA8)))))));
+    } else {
+        // |x| >= 1/.35
+        r = RB0 + s * (RB1 + s * (RB2 + s * (RB3 + s * (RB4 + s * (RB5 + s * RB6)))));
+        big_s = 1.0 + s * (SB1 + s * (SB2 + s * (SB3 + s * (SB4 + s * (SB5 + s * (SB6 + s * SB7))))));
+    }
+    let z = with_low_word(x, 0);
+    exp(-z * z - 0.5625) * exp((z - x) * (z + x) + r / big_s) / x
+}
+
+/// The error function.
+pub fn erf(x: f64) -> f64 {
+    let mut ix = high_word(x);
+    let sign = ix >> 31 != 0;
+    ix &= 0x7fff_ffff;
+    if ix >= 0x7ff0_0000 {
+        // erf(nan) = nan, erf(+-inf) = +-1
+        return if sign { -1.0 } else { 1.0 } + 1.0 / x;
+    }
+    if ix < 0x3feb_0000 {
+        // |x| < 0.84375
+        if ix < 0x3e30_0000 {
+            // |x| < 2^-28; avoid underflow.
+            return 0.125 * (8.0 * x + EFX8 * x);
+        }
+        let z = x * x;
+        let r = PP0 + z * (PP1 + z * (PP2 + z * (PP3 + z * PP4)));
+        let s = 1.0 + z * (QQ1 + z * (QQ2 + z * (QQ3 + z * (QQ4 + z * QQ5))));
+        let y = r / s;
+        return x + x * y;
+    }
+    let y = if ix < 0x4018_0000 {
+        // 0.84375 <= |x| < 6
+        1.0 - erfc2(ix, x)
+    } else {
+        1.0 - pow2(-1022)
+    };
+    if sign { -y } else { y }
+}
@@ -17,5 +17,8 @@ pub mod intrinsics;
 pub mod intrinsics { }
 
 pub mod kernel;
+pub mod math;
 pub mod platform;
+pub mod ptr;
+pub mod rand;
 pub mod spec_param;
@@ -87,6 +87,71 @@ pub mod hsa {
             Ok(v)
         }
     }
+    /// Static properties of each processor. These are fixed by the ISA; things which
+    /// differ between boards with the same processor (CU count, clocks, memory size,
+    /// f64 rate) have to be queried from the HSA agent instead.
+    impl AmdGcn {
+        /// The `(major, minor, stepping)` of the GFX ISA version.
+        #[inline(always)]
+        pub const fn gfx_version(self) -> (u32, u32, u32) {
+            use self::AmdGcn::*;
+
+            match self {
+                Gfx801 | Carrizo => (8, 0, 1),
+                Gfx802 | Iceland | Tonga => (8, 0, 2),
+                Gfx803 | Fiji | Polaris10 | Polaris11 => (8, 0, 3),
+                Gfx810 | Stoney => (8, 1, 0),
+
+                Gfx900 => (9, 0, 0),
+                Gfx902 => (9, 0, 2),
+                Gfx904 => (9, 0, 4),
+                Gfx906 => (9, 0, 6),
+                Gfx909 => (9, 0, 9),
+            }
+        }
+        /// The canonical `gfxNNN` processor name; aliases like `fiji` map to
+        /// their GFX version.
+        #[inline(always)]
+        pub const fn gfx_name(self) -> &'static str {
+            use self::AmdGcn::*;
+
+            match self {
+                Gfx801 | Carrizo => "gfx801",
+                Gfx802 | Iceland | Tonga => "gfx802",
+                Gfx803 | Fiji | Polaris10 | Polaris11 => "gfx803",
+                Gfx810 | Stoney => "gfx810",
+
+                Gfx900 => "gfx900",
+                Gfx902 => "gfx902",
+                Gfx904 => "gfx904",
+                Gfx906 => "gfx906",
+                Gfx909 => "gfx909",
+            }
+        }
+
+        /// The native wavefront size. All GFX8/GFX9 processors are wave64 only.
+        #[inline(always)]
+        pub const fn wavefront_size(self) -> u32 { 64 }
+        /// The LDS available to a single workgroup, in bytes.
+        #[inline(always)]
+        pub const fn lds_size(self) -> u32 { 64 * 1024 }
+        #[inline(always)]
+        pub const fn max_workgroup_size(self) -> u32 { 1024 }
+
+        /// Packed 16-bit math (VOP3P), ie `v_pk_*` instructions.
+        #[inline(always)]
+        pub const fn has_packed_math(self) -> bool {
+            self.gfx_version().0 >= 9
+        }
+        /// The `v_dot*` instructions.
+        #[inline(always)]
+        pub const fn has_dot_insts(self) -> bool {
+            match self {
+                AmdGcn::Gfx906 => true,
+                _ => false,
+            }
+        }
+    }
 
     #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
     pub enum AmdGpu {
@@ -0,0 +1,408 @@
+//! Pointer wrappers which record which address space (segment) the pointee lives
+//! in. Accelerators like AMDGPU have several disjoint address spaces which are
+//! all reachable through the flat address space; segment specific accesses are
+//! faster, but a plain `*mut T` carries no information about the segment, so
+//! everything ends up going through flat accesses.
+//!
+//! These types are `#[repr(transparent)]` over a raw pointer, so they can be used
+//! as kernel arguments in place of raw pointers. Conversions are only provided
+//! *toward* the flat address space, which is always valid. Going the other way
+//! is `unsafe`: the caller asserts the flat pointer actually points into the
+//! segment.
+//!
+//! The pointers themselves are still stored and passed around as flat pointers,
+//! but `read` and `write` are lowered by codegen to accesses in the tagged
+//! address space, so the backend can select segment specific instructions.
+//! Loads through `ConstPtr` are additionally marked invariant.
+
+use crate::cmp::{Eq, PartialEq};
+use crate::fmt;
+use crate::hash::{Hash, Hasher};
+use crate::marker::{Copy, PhantomData};
+
+#[cfg(not(bootstrap))]
+use crate::geobacter::intrinsics::{geobacter_addr_space_load_global,
+                                   geobacter_addr_space_load_constant,
+                                   geobacter_addr_space_load_local,
+                                   geobacter_addr_space_load_private,
+                                   geobacter_addr_space_store_global,
+                                   geobacter_addr_space_store_local,
+                                   geobacter_addr_space_store_private};
+#[cfg(bootstrap)]
+use self::bootstrap::*;
+
+/// The bootstrap compiler doesn't have the address space intrinsics; just use
+/// flat accesses.
+#[cfg(bootstrap)]
+mod bootstrap {
+    macro_rules! flat_access {
+        ($($load:ident,)*; $($store:ident,)*) => {
+            $(
+                #[inline(always)]
+                pub unsafe fn $load<T>(ptr: *const T) -> T {
+                    unsafe { ptr.read() }
+                }
+            )*
+            $(
+                #[inline(always)]
+                pub unsafe fn $store<T>(ptr: *mut T, v: T) {
+                    unsafe { ptr.write(v) }
+                }
+            )*
+        };
+    }
+    flat_access!(
+        geobacter_addr_space_load_global,
+        geobacter_addr_space_load_constant,
+        geobacter_addr_space_load_local,
+        geobacter_addr_space_load_private,
+        ;
+        geobacter_addr_space_store_global,
+        geobacter_addr_space_store_local,
+        geobacter_addr_space_store_private,
+    );
+}
+
+/// The address spaces a pointer wrapper can be tagged with. These correspond
+/// to the `AddrSpaceKind`s the compiler uses in the target spec: `Global` is
+/// "readwrite", `Constant` is "readonly", `Local` is "local" (the workgroup
+/// local memory, ie LDS on AMDGPU) and `Private` is "alloca".
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum AddrSpaceKind {
+    Flat,
+    Global,
+    Constant,
+    Local,
+    Private,
+}
+
+/// Implemented by the marker types below.
+pub trait AddrSpace: Copy + 'static {
+    const KIND: AddrSpaceKind;
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Global;
+impl AddrSpace for Global {
+    const KIND: AddrSpaceKind = AddrSpaceKind::Global;
+}
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Constant;
+impl AddrSpace for Constant {
+    const KIND: AddrSpaceKind = AddrSpaceKind::Constant;
+}
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Local;
+impl AddrSpace for Local {
+    const KIND: AddrSpaceKind = AddrSpaceKind::Local;
+}
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Private;
+impl AddrSpace for Private {
+    const KIND: AddrSpaceKind = AddrSpaceKind::Private;
+}
+
+macro_rules! addr_space_ptr {
+    ($(#[$attr:meta])* $name:ident, $space:ty, $raw:ty, $load:ident) => {
+
+$(#[$attr])*
+#[repr(transparent)]
+pub struct $name<T>($raw, PhantomData<$space>)
+    where T: ?Sized;
+impl<T> $name<T>
+    where T: ?Sized,
+{
+    /// The pointer must point into the address space this type is tagged with,
+    /// or be null.
+    #[inline(always)]
+    pub const unsafe fn new_unchecked(ptr: $raw) -> Self {
+        $name(ptr, PhantomData)
+    }
+    #[inline(always)]
+    pub const fn addr_space(&self) -> AddrSpaceKind {
+        <$space as AddrSpace>::KIND
+    }
+    #[inline(always)]
+    pub fn is_null(self) -> bool {
+        self.0.is_null()
+    }
+    #[inline(always)]
+    pub const fn as_flat(self) -> *const T {
+        self.0 as *const T
+    }
+}
+impl<T> $name<T> {
+    /// The offset pointer must stay inside the same allocation, just like
+    /// `pointer::add`.
+    #[inline(always)]
+    pub unsafe fn add(self, count: usize) -> Self {
+        $name(unsafe { self.0.add(count) }, PhantomData)
+    }
+    #[inline(always)]
+    pub unsafe fn read(self) -> T {
+        unsafe { $load(self.0) }
+    }
+}
+impl<T> Clone for $name<T>
+    where T: ?Sized,
+{
+    #[inline(always)]
+    fn clone(&self) -> Self { *self }
+}
+impl<T> Copy for $name<T>
+    where T: ?Sized,
+{ }
+impl<T> PartialEq for $name<T>
+    where T: ?Sized,
+{
+    #[inline(always)]
+    fn eq(&self, rhs: &Self) -> bool {
+        self.0 == rhs.0
+    }
+}
+impl<T> Eq for $name<T>
+    where T: ?Sized,
+{ }
+impl<T> Hash for $name<T>
+    where T: ?Sized,
+{
+    fn hash<H>(&self, hasher: &mut H)
+        where H: Hasher,
+    {
+        self.0.hash(hasher)
+    }
+}
+impl<T> fmt::Debug for $name<T>
+    where T: ?Sized,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple(stringify!($name))
+            .field(&self.0)
+            .finish()
+    }
+}
+impl<T> From<$name<T>> for *const T
+    where T: ?Sized,
+{
+    #[inline(always)]
+    fn from(v: $name<T>) -> *const T {
+        v.as_flat()
+    }
+}
+
+    };
+}
+macro_rules! addr_space_ptr_mut {
+    ($($name:ident => $store:ident,)*) => {$(
+
+impl<T> $name<T>
+    where T: ?Sized,
+{
+    #[inline(always)]
+    pub const fn as_flat_mut(self) -> *mut T {
+        self.0
+    }
+}
+impl<T> $name<T> {
+    #[inline(always)]
+    pub unsafe fn write(self, v: T) {
+        unsafe { $store(self.0, v) }
+    }
+}
+impl<T> From<$name<T>> for *mut T
+    where T: ?Sized,
+{
+    #[inline(always)]
+    fn from(v: $name<T>) -> *mut T {
+        v.as_flat_mut()
+    }
+}
+
+    )*};
+}
+
+addr_space_ptr!(
+    /// A pointer into device global memory.
+    GlobalPtr, Global, *mut T, geobacter_addr_space_load_global
+);
+addr_space_ptr!(
+    /// A pointer into the read only constant segment. The memory must not change
+    /// while any kernel reading it runs, so this doesn't have a way to write.
+    ConstPtr, Constant, *const T, geobacter_addr_space_load_constant
+);
+addr_space_ptr!(
+    /// A pointer into workgroup local memory (LDS on AMDGPU). Only meaningful
+    /// within the workgroup which created it.
+    LdsPtr, Local, *mut T, geobacter_addr_space_load_local
+);
+addr_space_ptr!(
+    /// A pointer into the private (scratch) memory of the current workitem. Never
+    /// pass this to another workitem.
+    PrivatePtr, Private, *mut T, geobacter_addr_space_load_private
+);
+addr_space_ptr_mut!(
+    GlobalPtr => geobacter_addr_space_store_global,
+    LdsPtr => geobacter_addr_space_store_local,
+    PrivatePtr => geobacter_addr_space_store_private,
+);
+
+/// A read only buffer kernel argument. The buffer lives in the constant segment;
+/// `load` goes through `ConstPtr::read`, so it is emitted in the constant
+/// address space and marked invariant, which lets accelerators serve uniform
+/// loads from their scalar caches. The device must not write to the buffer while
+/// any kernel using it is running.
+///
+/// Plain `&[T]` kernel parameters aren't moved into the constant segment; they
+/// only get the `noalias readonly` parameter attributes every shared reference
+/// gets. Use this type to opt in explicitly.
+#[repr(C)]
+pub struct ConstBuffer<'a, T> {
+    ptr: ConstPtr<T>,
+    len: usize,
+    _lt: PhantomData<&'a [T]>,
+}
+impl<'a, T> ConstBuffer<'a, T> {
+    /// The slice must be in memory the device can read through the constant
+    /// address space.
+    #[inline(always)]
+    pub unsafe fn from_slice(slice: &'a [T]) -> Self {
+        ConstBuffer {
+            ptr: unsafe { ConstPtr::new_unchecked(slice.as_ptr()) },
+            len: slice.len(),
+            _lt: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub const fn as_ptr(&self) -> ConstPtr<T> {
+        self.ptr
+    }
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// A flat view of the buffer. Loads through the returned slice are ordinary
+    /// flat loads; prefer `load` for values which should go through the
+    /// constant segment.
+    #[inline(always)]
+    pub fn as_slice(&self) -> &'a [T] {
+        unsafe { crate::slice::from_raw_parts(self.ptr.as_flat(), self.len) }
+    }
+    #[inline(always)]
+    pub fn get(&self, idx: usize) -> Option<&'a T> {
+        self.as_slice().get(idx)
+    }
+    /// Load the element at `idx` through the constant address space.
+    #[inline(always)]
+    pub fn load(&self, idx: usize) -> Option<T>
+        where T: Copy,
+    {
+        if idx < self.len {
+            Some(unsafe { self.ptr.add(idx).read() })
+        } else {
+            None
+        }
+    }
+}
+impl<'a, T> Clone for ConstBuffer<'a, T> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<'a, T> Copy for ConstBuffer<'a, T> { }
+impl<'a, T> crate::ops::Deref for ConstBuffer<'a, T> {
+    type Target = [T];
+    #[inline(always)]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+impl<'a, T> fmt::Debug for ConstBuffer<'a, T>
+    where T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+/// The index and pointer width of a single address space on the current target.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct AddrSpaceInfo {
+    pub index: u32,
+    pub pointer_bits: u32,
+}
+impl AddrSpaceInfo {
+    /// Returns false if the target doesn't define this address space.
+    #[inline(always)]
+    pub const fn is_present(&self) -> bool {
+        self.pointer_bits != 0
+    }
+}
+
+/// The address space layout of the target the current function is being
+/// compiled for. On the host all of these are address space zero.
+///
+/// The layout of this type is shared with the compiler; don't reorder the fields.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct TargetAddrSpaces {
+    pub flat: AddrSpaceInfo,
+    pub global: AddrSpaceInfo,
+    pub constant: AddrSpaceInfo,
+    pub local: AddrSpaceInfo,
+    pub private: AddrSpaceInfo,
+    pub instruction: AddrSpaceInfo,
+}
+impl TargetAddrSpaces {
+    #[inline(always)]
+    pub const fn get(&self, kind: AddrSpaceKind) -> Option<AddrSpaceInfo> {
+        let info = match kind {
+            AddrSpaceKind::Flat => self.flat,
+            AddrSpaceKind::Global => self.global,
+            AddrSpaceKind::Constant => self.constant,
+            AddrSpaceKind::Local => self.local,
+            AddrSpaceKind::Private => self.private,
+        };
+        if info.is_present() { Some(info) } else { None }
+    }
+}
+
+#[cfg(not(bootstrap))]
+#[inline(always)]
+pub const fn target_addr_spaces() -> TargetAddrSpaces {
+    use crate::mem::{size_of, transmute, };
+
+    extern "rust-intrinsic" {
+        #[rustc_const_unstable(feature = "geobacter", issue = "none")]
+        fn geobacter_addr_spaces()
+            -> &'static [u32; size_of::<TargetAddrSpaces>() / size_of::<u32>()];
+    }
+
+    let p: &'static TargetAddrSpaces = unsafe {
+        let r = geobacter_addr_spaces();
+        transmute(r)
+    };
+    *p
+}
+#[cfg(bootstrap)]
+#[inline(always)]
+pub const fn target_addr_spaces() -> TargetAddrSpaces {
+    const HOST: AddrSpaceInfo = AddrSpaceInfo {
+        index: 0,
+        pointer_bits: crate::mem::size_of::<usize>() as u32 * 8,
+    };
+    TargetAddrSpaces {
+        flat: HOST,
+        global: HOST,
+        constant: HOST,
+        local: HOST,
+        private: HOST,
+        instruction: HOST,
+    }
+}
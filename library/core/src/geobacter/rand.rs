@@ -0,0 +1,162 @@
+//! Counter based random number generation. A counter based generator has no
+//! state besides a key and a counter, so every workitem can construct its own
+//! independent generator from a shared seed and its global id; there's no
+//! per-workitem state to allocate and carry between dispatches.
+//!
+//! `Philox4x32` is Philox4x32-10 from "Parallel Random Numbers: As Easy as
+//! 1, 2, 3" (Salmon et al., SC11), and produces the same streams as the
+//! Random123 library.
+//!
+//! Typical use: the host keeps an `RngSeed`, passes it to each dispatch as a
+//! kernel argument, and then calls `RngSeed::advance` with the number of
+//! workitems it launched, so the next dispatch doesn't reuse any streams. The
+//! kernel calls `seed.rng(global_linear_id as u64)`.
+
+use crate::fmt;
+
+const M0: u32 = 0xd251_1f53;
+const M1: u32 = 0xcd9e_8d57;
+const W0: u32 = 0x9e37_79b9;
+const W1: u32 = 0xbb67_ae85;
+const ROUNDS: usize = 10;
+
+#[inline(always)]
+fn mul_hi_lo(a: u32, b: u32) -> (u32, u32) {
+    let p = a as u64 * b as u64;
+    ((p >> 32) as u32, p as u32)
+}
+
+/// A Philox4x32-10 generator. Each counter value produces four `u32`s; the
+/// counter is 128 bits wide, so a single stream never wraps in practice.
+#[derive(Clone)]
+pub struct Philox4x32 {
+    key: [u32; 2],
+    counter: [u32; 4],
+    buf: [u32; 4],
+    /// The next unused element of `buf`; 4 means `buf` is exhausted.
+    idx: usize,
+}
+impl Philox4x32 {
+    /// Create the generator for stream `stream` of `seed`. The stream occupies
+    /// the upper half of the counter, so each stream gets 2^64 blocks.
+    #[inline(always)]
+    pub const fn new(seed: u64, stream: u64) -> Self {
+        Philox4x32 {
+            key: [seed as u32, (seed >> 32) as u32],
+            counter: [0, 0, stream as u32, (stream >> 32) as u32],
+            buf: [0; 4],
+            idx: 4,
+        }
+    }
+
+    /// The raw Philox4x32-10 bijection.
+    #[inline]
+    pub fn block(key: [u32; 2], counter: [u32; 4]) -> [u32; 4] {
+        let mut key = key;
+        let mut ctr = counter;
+        for round in 0..ROUNDS {
+            if round != 0 {
+                key[0] = key[0].wrapping_add(W0);
+                key[1] = key[1].wrapping_add(W1);
+            }
+            let (hi0, lo0) = mul_hi_lo(M0, ctr[0]);
+            let (hi1, lo1) = mul_hi_lo(M1, ctr[2]);
+            ctr = [hi1 ^ ctr[1] ^ key[0], lo1, hi0 ^ ctr[3] ^ key[1], lo0];
+        }
+        ctr
+    }
+
+    #[inline(always)]
+    fn bump_counter(&mut self) {
+        let lo = (self.counter[0] as u64 | (self.counter[1] as u64) << 32)
+            .wrapping_add(1);
+        self.counter[0] = lo as u32;
+        self.counter[1] = (lo >> 32) as u32;
+    }
+
+    /// Skip ahead `blocks` blocks (of four `u32`s each) in the stream. Any
+    /// values remaining from the current block are discarded.
+    #[inline]
+    pub fn skip_blocks(&mut self, blocks: u64) {
+        let lo = (self.counter[0] as u64 | (self.counter[1] as u64) << 32)
+            .wrapping_add(blocks);
+        self.counter[0] = lo as u32;
+        self.counter[1] = (lo >> 32) as u32;
+        self.idx = 4;
+    }
+
+    /// Generate the next four values at once. Doesn't touch values left over
+    /// from a previous block.
+    #[inline]
+    pub fn next_block(&mut self) -> [u32; 4] {
+        let out = Self::block(self.key, self.counter);
+        self.bump_counter();
+        out
+    }
+
+    #[inline]
+    pub fn next_u32(&mut self) -> u32 {
+        if self.idx >= 4 {
+            self.buf = self.next_block();
+            self.idx = 0;
+        }
+        let v = self.buf[self.idx];
+        self.idx += 1;
+        v
+    }
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        let lo = self.next_u32() as u64;
+        let hi = self.next_u32() as u64;
+        hi << 32 | lo
+    }
+    /// A uniformly distributed value in `[0, 1)`.
+    #[inline]
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+    }
+    /// A uniformly distributed value in `[0, 1)`.
+    #[inline]
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+}
+impl fmt::Debug for Philox4x32 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Philox4x32")
+            .field("key", &self.key)
+            .field("counter", &self.counter)
+            .finish()
+    }
+}
+
+/// A seed plus the first stream not yet handed out. Intended to be passed to
+/// kernels by value.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct RngSeed {
+    pub seed: u64,
+    pub stream_offset: u64,
+}
+impl RngSeed {
+    #[inline(always)]
+    pub const fn new(seed: u64) -> Self {
+        RngSeed {
+            seed,
+            stream_offset: 0,
+        }
+    }
+
+    /// The generator for the `stream`th stream after `stream_offset`, usually
+    /// indexed by the global id of the workitem.
+    #[inline(always)]
+    pub const fn rng(&self, stream: u64) -> Philox4x32 {
+        Philox4x32::new(self.seed, self.stream_offset.wrapping_add(stream))
+    }
+
+    /// Mark the next `streams` streams as used.
+    #[inline(always)]
+    pub fn advance(&mut self, streams: u64) {
+        self.stream_offset = self.stream_offset.wrapping_add(streams);
+    }
+}
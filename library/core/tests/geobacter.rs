@@ -0,0 +1,424 @@
+use core::geobacter::math::*;
+use core::geobacter::ptr::*;
+use core::geobacter::rand::*;
+
+/// The distance between `a` and `b` in units of `b`'s ulp.
+fn ulps(a: f64, b: f64) -> f64 {
+    if a == b || (a.is_nan() && b.is_nan()) {
+        return 0.0;
+    }
+    let b_abs = b.abs().max(f64::MIN_POSITIVE);
+    let ulp = f64::from_bits(b_abs.to_bits() + 1) - b_abs;
+    ((a - b) / ulp).abs()
+}
+
+fn assert_close(what: &str, x: f64, a: f64, b: f64) {
+    assert!(ulps(a, b) <= 1.0, "{}({:e}): {:e} != {:e}", what, x, a, b);
+}
+
+#[test]
+fn math_exp() {
+    for i in -1500..=1500 {
+        let x = i as f64 * 0.4729;
+        assert_close("exp", x, exp(x), x.exp());
+    }
+    for &x in &[0.0, -0.0, 1e-300, -1e-300, 709.78, 709.79, -745.1, -745.2] {
+        assert_close("exp", x, exp(x), x.exp());
+    }
+    assert_eq!(exp(f64::INFINITY), f64::INFINITY);
+    assert_eq!(exp(f64::NEG_INFINITY), 0.0);
+    assert!(exp(f64::NAN).is_nan());
+}
+
+#[test]
+fn math_ln() {
+    for i in 1..=3000 {
+        let x = (i as f64 * 0.2371).exp2() * 1e-300;
+        assert_close("ln", x, ln(x), x.ln());
+    }
+    for &x in &[1.0, f64::MIN_POSITIVE, 5e-324, 1e-310, f64::MAX] {
+        assert_close("ln", x, ln(x), x.ln());
+    }
+    assert_eq!(ln(0.0), f64::NEG_INFINITY);
+    assert_eq!(ln(-0.0), f64::NEG_INFINITY);
+    assert_eq!(ln(f64::INFINITY), f64::INFINITY);
+    assert!(ln(-1.0).is_nan());
+    assert!(ln(f64::NAN).is_nan());
+}
+
+#[test]
+fn math_powf() {
+    for i in 0..=100 {
+        let x = i as f64 * 0.1173;
+        for j in -100..=100 {
+            let y = j as f64 * 3.217;
+            assert_close("powf", x, powf(x, y), x.powf(y));
+        }
+    }
+    // Representable integer results are exact.
+    for x in -20..=20 {
+        for y in 0..=12 {
+            let (x, y) = (x as f64, y as f64);
+            assert_eq!(powf(x, y), x.powf(y), "powf({}, {})", x, y);
+        }
+    }
+
+    assert_eq!(powf(f64::NAN, 0.0), 1.0);
+    assert_eq!(powf(1.0, f64::NAN), 1.0);
+    assert_eq!(powf(-1.0, f64::INFINITY), 1.0);
+    assert_eq!(powf(-0.0, -1.0), f64::NEG_INFINITY);
+    assert_eq!(powf(-0.0, -2.0), f64::INFINITY);
+    assert_eq!(powf(2.0, -1075.0), 0.0);
+    assert_eq!(powf(2.0, 1024.0), f64::INFINITY);
+    assert!(powf(-8.0, 1.0 / 3.0).is_nan());
+}
+
+#[test]
+fn math_erf() {
+    // Correctly rounded reference values.
+    let expected = [
+        (0.1, 0.1124629160182849),
+        (0.5, 0.5204998778130465),
+        (1.0, 0.8427007929497149),
+        (1.5, 0.9661051464753108),
+        (2.5, 0.999593047982555),
+        (4.0, 0.9999999845827421),
+        (-0.75, -0.7111556336535151),
+    ];
+    for &(x, e) in expected.iter() {
+        assert_close("erf", x, erf(x), e);
+    }
+    assert_eq!(erf(0.0), 0.0);
+    assert_eq!(erf(6.0), 1.0);
+    assert_eq!(erf(f64::INFINITY), 1.0);
+    assert_eq!(erf(f64::NEG_INFINITY), -1.0);
+    assert!(erf(f64::NAN).is_nan());
+}
+
+#[test]
+fn philox_known_answers() {
+    // From the Random123 known answer tests.
+    assert_eq!(Philox4x32::block([0, 0], [0; 4]),
+               [0x6627e8d5, 0xe169c58d, 0xbc57ac4c, 0x9b00dbd8]);
+    assert_eq!(Philox4x32::block([!0, !0], [!0; 4]),
+               [0x408f276d, 0x41c83b0e, 0xa20bc7c6, 0x6d5451fd]);
+    assert_eq!(Philox4x32::block([0xa4093822, 0x299f31d0],
+                                 [0x243f6a88, 0x85a308d3, 0x13198a2e, 0x03707344]),
+               [0xd16cfe09, 0x94fdcceb, 0x5001e420, 0x24126ea1]);
+}
+
+#[test]
+fn philox_streams() {
+    let seed = RngSeed::new(42);
+    let mut a = seed.rng(0);
+    let mut b = seed.rng(0);
+    b.skip_blocks(1);
+    for _ in 0..4 {
+        a.next_u32();
+    }
+    assert_eq!(a.next_u32(), b.next_u32());
+
+    let mut advanced = seed;
+    advanced.advance(7);
+    assert_eq!(advanced.rng(0).next_u64(), seed.rng(7).next_u64());
+    assert_ne!(seed.rng(0).next_u64(), seed.rng(1).next_u64());
+
+    for _ in 0..1000 {
+        let v = a.next_f64();
+        assert!(v >= 0.0 && v < 1.0);
+        let v = a.next_f32();
+        assert!(v >= 0.0 && v < 1.0);
+    }
+}
+
+#[test]
+fn target_addr_spaces_get() {
+    let info = |index| AddrSpaceInfo { index, pointer_bits: 64 };
+    let missing = AddrSpaceInfo { index: !0, pointer_bits: 0 };
+    let spaces = TargetAddrSpaces {
+        flat: info(0),
+        global: info(1),
+        constant: info(4),
+        local: missing,
+        private: info(5),
+        instruction: info(0),
+    };
+    assert_eq!(spaces.get(AddrSpaceKind::Flat), Some(info(0)));
+    assert_eq!(spaces.get(AddrSpaceKind::Global), Some(info(1)));
+    assert_eq!(spaces.get(AddrSpaceKind::Constant), Some(info(4)));
+    assert_eq!(spaces.get(AddrSpaceKind::Local), None);
+    assert_eq!(spaces.get(AddrSpaceKind::Private), Some(info(5)));
+
+    // Everything is in address space zero on the host.
+    let host = AddrSpaceInfo { index: 0, pointer_bits: usize::BITS };
+    let spaces = target_addr_spaces();
+    for &kind in &[AddrSpaceKind::Flat, AddrSpaceKind::Global, AddrSpaceKind::Constant,
+                   AddrSpaceKind::Local, AddrSpaceKind::Private] {
+        assert_eq!(spaces.get(kind), Some(host));
+    }
+}
+
+#[test]
+fn const_buffer() {
+    let data = [1u32, 2, 3];
+    let buf = unsafe { ConstBuffer::from_slice(&data) };
+    assert_eq!(buf.len(), 3);
+    assert!(!buf.is_empty());
+    assert_eq!(buf.get(1), Some(&2));
+    assert_eq!(buf.get(3), None);
+    assert_eq!(buf.load(2), Some(3));
+    assert_eq!(buf.load(3), None);
+    assert_eq!(&*buf, &data[..]);
+
+    let empty = unsafe { ConstBuffer::<u32>::from_slice(&[]) };
+    assert_eq!(empty.len(), 0);
+    assert!(empty.is_empty());
+    assert_eq!(empty.get(0), None);
+    assert_eq!(empty.load(0), None);
+}
+
+// The AMDGPU helpers are only built by the stage2 compiler.
+#[cfg(stage2)]
+mod amdgpu {
+    use core::geobacter::amdgpu::assert::*;
+    use core::geobacter::amdgpu::buffer::*;
+    use core::geobacter::amdgpu::clock::*;
+    use core::geobacter::amdgpu::exception::*;
+    use core::geobacter::amdgpu::wavefront::*;
+    use core::geobacter::amdgpu::workitem::*;
+
+    #[test]
+    fn extent3() {
+        let e = Extent3::new(4, 3, 2);
+        assert_eq!(e.len(), 24);
+        assert!(!e.is_empty());
+        assert!(Extent3::new(4, 0, 2).is_empty());
+        assert_eq!(Extent3::new_1d(5), Extent3::new(5, 1, 1));
+        assert_eq!(Extent3::new_2d(5, 6), Extent3::new(5, 6, 1));
+        assert_eq!(<[u32; 3]>::from(e), [4, 3, 2]);
+        assert_eq!(Extent3::from([4, 3, 2]), e);
+    }
+
+    #[test]
+    fn extent3_contains() {
+        let e = Extent3::new(4, 3, 2);
+        assert!(e.contains([0, 0, 0]));
+        assert!(e.contains([3, 2, 1]));
+        assert!(!e.contains([4, 0, 0]));
+        assert!(!e.contains([0, 3, 0]));
+        assert!(!e.contains([0, 0, 2]));
+        assert!(!Extent3::default().contains([0, 0, 0]));
+    }
+
+    #[test]
+    fn extent3_linearize() {
+        let e = Extent3::new(4, 3, 2);
+        let mut expected = 0;
+        for z in 0..2 {
+            for y in 0..3 {
+                for x in 0..4 {
+                    assert_eq!(e.linearize([x, y, z]), expected);
+                    expected += 1;
+                }
+            }
+        }
+        assert_eq!(expected, e.len());
+    }
+
+    #[test]
+    fn extent3_div_ceil() {
+        let wg = Extent3::new(64, 4, 1);
+        assert_eq!(Extent3::new(128, 4, 1).div_ceil(wg), Extent3::new(2, 1, 1));
+        assert_eq!(Extent3::new(129, 5, 3).div_ceil(wg), Extent3::new(3, 2, 3));
+        assert_eq!(Extent3::new(0, 0, 0).div_ceil(wg), Extent3::new(0, 0, 0));
+        assert_eq!(Extent3::new(!0, !0, !0).div_ceil(wg),
+                   Extent3::new(!0 / 64 + 1, !0 / 4 + 1, !0));
+    }
+
+    #[test]
+    fn grid_stride_loop() {
+        let ids: Vec<_> = GridStrideLoop::new(3, 8, 30).collect();
+        assert_eq!(ids, [3, 11, 19, 27]);
+        assert_eq!(GridStrideLoop::new(3, 8, 30).size_hint(), (4, Some(4)));
+        assert_eq!(GridStrideLoop::new(3, 8, 27).size_hint(), (3, Some(3)));
+        assert_eq!(GridStrideLoop::new(30, 8, 30).next(), None);
+        assert_eq!(GridStrideLoop::new(0, 0, 1).size_hint(), (0, None));
+
+        let mut it = GridStrideLoop::new(!0 - 1, 8, !0);
+        assert_eq!(it.next(), Some(!0 - 1));
+        assert_eq!(it.next(), None);
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn lane_mask() {
+        let m = LaneMask::from_bits(0x8000_0000_0000_0105);
+        assert_eq!(m.bits(), 0x8000_0000_0000_0105);
+        assert_eq!(m.count(), 4);
+        assert!(!m.is_empty());
+        assert!(m.contains(0));
+        assert!(!m.contains(1));
+        assert!(m.contains(2));
+        assert!(m.contains(8));
+        assert!(m.contains(63));
+        assert!(!m.contains(64));
+        assert_eq!(m.first(), Some(0));
+        assert_eq!(LaneMask::from_bits(0x100).first(), Some(8));
+
+        let empty = LaneMask::default();
+        assert!(empty.is_empty());
+        assert_eq!(empty.count(), 0);
+        assert_eq!(empty.first(), None);
+    }
+
+    #[test]
+    fn lane_mask_lanes() {
+        let m = LaneMask::from_bits(0x8000_0000_0000_0105);
+        let mut lanes = m.lanes();
+        assert_eq!(lanes.size_hint(), (4, Some(4)));
+        assert_eq!(lanes.next(), Some(0));
+        assert_eq!(lanes.size_hint(), (3, Some(3)));
+        assert_eq!(lanes.collect::<Vec<_>>(), [2, 8, 63]);
+
+        let mut lanes = LaneMask::default().lanes();
+        assert_eq!(lanes.size_hint(), (0, Some(0)));
+        assert_eq!(lanes.next(), None);
+        assert_eq!(lanes.next(), None);
+        assert_eq!(LaneMask::from_bits(!0).lanes().count(), 64);
+    }
+
+    #[test]
+    fn clock_ticks_to_ns() {
+        assert_eq!(ticks_to_ns(12345, 0), 0);
+        assert_eq!(ticks_to_ns(0, 100_000_000), 0);
+        assert_eq!(ticks_to_ns(100_000_000, 100_000_000), 1_000_000_000);
+        assert_eq!(ticks_to_ns(300_000_000, 100_000_000), 3_000_000_000);
+        assert_eq!(ticks_to_ns(1, 100_000_000), 10);
+        assert_eq!(ticks_to_ns(250_000_007, 100_000_000), 2_500_000_070);
+        assert_eq!(ticks_to_ns(1, 3), 333_333_333);
+        assert_eq!(ticks_to_ns(u64::MAX, 1_000_000_000), u64::MAX);
+        assert_eq!(ticks_to_ns(u64::MAX, 100_000_000), u64::MAX);
+        assert_eq!(ticks_to_ns(u64::MAX, 1), u64::MAX);
+        assert_eq!(ticks_to_duration(250_000_007, 100_000_000),
+                   core::time::Duration::new(2, 500_000_070));
+    }
+
+    #[test]
+    fn assert_file_id() {
+        assert_eq!(file_id(""), 0x811c9dc5);
+        assert_eq!(file_id("a"), 0xe40c292c);
+        assert_eq!(file_id("foobar"), 0xbf9cf968);
+        assert_ne!(file_id("src/lib.rs"), file_id("src/main.rs"));
+    }
+
+    #[test]
+    fn assert_slot() {
+        let slot = AssertSlot::new();
+        assert_eq!(slot.info(), None);
+        assert_eq!(AssertSlot::default().info(), None);
+
+        let info = AssertInfo {
+            kind: AssertKind::Assert,
+            file: file_id("src/kernel.rs"),
+            line: 42,
+            column: 5,
+            kernel_object: 0x1234_5678_9abc_def0,
+            workgroup_id: [1, 2, 3],
+            workitem_id: [4, 5, 6],
+            failed_lanes: LaneMask::from_bits(0x8000_0000_0000_0010),
+            active_lanes: LaneMask::from_bits(!0),
+        };
+        assert!(slot.record_info(&info));
+        assert_eq!(slot.info(), Some(info));
+
+        // only the first failure is kept.
+        let second = AssertInfo { kind: AssertKind::Bounds, line: 43, ..info };
+        assert!(!slot.record_info(&second));
+        assert_eq!(slot.info(), Some(info));
+
+        slot.reset();
+        assert_eq!(slot.info(), None);
+        assert!(slot.record_info(&second));
+        assert_eq!(slot.info(), Some(second));
+    }
+
+    #[test]
+    fn assert_kind() {
+        for &kind in &[AssertKind::Assert, AssertKind::Bounds, AssertKind::Panic] {
+            assert_eq!(AssertKind::from_u32(kind as u32), Some(kind));
+        }
+        assert_eq!(AssertKind::from_u32(0), None);
+        assert_eq!(AssertKind::from_u32(4), None);
+    }
+
+    #[test]
+    fn buffer_descriptor() {
+        let mut data = [1u32, 2, 3];
+        let buf = unsafe { BufferResource::new(&data) };
+        let addr = data.as_ptr() as usize as u64;
+        let desc = buf.descriptor();
+        assert_eq!(desc[0], addr as u32);
+        assert_eq!(desc[1], (addr >> 32) as u32 & 0xffff);
+        assert_eq!(desc[2], 12);
+        assert_eq!(desc[3], 0x27FAC);
+        assert_eq!(buf.len(), 3);
+        assert!(!buf.is_empty());
+
+        let ptr = 0x1234_5678_9abcusize as *const f32;
+        let buf = unsafe { BufferResource::from_raw_parts(ptr, 0) };
+        assert_eq!(buf.descriptor(), [0x5678_9abc, 0x1234, 0, 0x27FAC]);
+        assert_eq!(buf.len(), 0);
+        assert!(buf.is_empty());
+
+        let buf = unsafe { BufferResourceMut::new(&mut data) };
+        assert_eq!(buf.len(), 3);
+        assert_eq!(buf.as_resource().descriptor()[2], 12);
+    }
+
+    #[test]
+    fn buffer_max_size() {
+        let len = u32::MAX as usize / 4;
+        let buf = unsafe { BufferResource::<u32>::from_raw_parts(4usize as *const _, len) };
+        assert_eq!(buf.descriptor()[2], (len * 4) as u32);
+        assert_eq!(buf.len(), len);
+    }
+
+    #[test]
+    #[should_panic(expected = "buffer too large for a V#")]
+    fn buffer_too_large() {
+        let len = u32::MAX as usize / 4 + 1;
+        unsafe { BufferResource::<u32>::from_raw_parts(4usize as *const _, len) };
+    }
+
+    #[test]
+    fn buffer_offset_clamp() {
+        assert_eq!(BufferResource::<u32>::offset_of(0), 0);
+        assert_eq!(BufferResource::<u32>::offset_of(3), 12);
+        assert_eq!(BufferResource::<u32>::offset_of(u32::MAX as usize / 4), u32::MAX - 3);
+        assert_eq!(BufferResource::<u32>::offset_of(u32::MAX as usize / 4 + 1), u32::MAX);
+        assert_eq!(BufferResource::<u32>::offset_of(usize::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn exceptions() {
+        assert_eq!(Exceptions::FP_MASK, 0x3f);
+        assert_eq!(Exceptions::from_bits(!0).bits(), 0x1ff);
+
+        let e = Exceptions::from_bits(Exceptions::INVALID | Exceptions::OVERFLOW);
+        assert!(e.invalid());
+        assert!(e.overflow());
+        assert!(!e.div_by_zero());
+        assert!(!e.inexact());
+        assert!(e.contains(Exceptions::INVALID | Exceptions::OVERFLOW));
+        assert!(!e.contains(Exceptions::INVALID | Exceptions::UNDERFLOW));
+        assert!(e.contains(0));
+        assert!(e.any_fp());
+
+        assert!(!Exceptions::default().any_fp());
+        assert!(!Exceptions::from_bits(Exceptions::INEXACT).any_fp());
+        assert!(Exceptions::from_bits(Exceptions::INEXACT).inexact());
+        assert!(!Exceptions::from_bits(Exceptions::INT_DIV_BY_ZERO).any_fp());
+        assert!(!Exceptions::from_bits(Exceptions::MEM_VIOLATION).any_fp());
+        assert!(Exceptions::from_bits(Exceptions::INPUT_DENORMAL).any_fp());
+    }
+}
@@ -23,6 +23,7 @@
 #![feature(fixed_size_array)]
 #![feature(flt2dec)]
 #![feature(fmt_internals)]
+#![feature(geobacter)]
 #![feature(hashmap_internals)]
 #![feature(try_find)]
 #![feature(is_sorted)]
@@ -74,6 +75,7 @@ mod char;
 mod clone;
 mod cmp;
 mod fmt;
+mod geobacter;
 mod hash;
 mod intrinsics;
 mod iter;
@@ -0,0 +1,15 @@
+#![feature(lang_items, no_core)]
+#![no_core]
+
+#[lang="sized"]
+trait Sized { }
+
+#[lang="copy"]
+trait Copy { }
+
+impl Copy for f32 { }
+
+#[no_mangle]
+pub fn id(x: f32) -> f32 {
+    x
+}